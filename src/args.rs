@@ -1,14 +1,23 @@
-use anyhow::{anyhow, Result};
+use anyhow::{anyhow, Context, Result};
 use clap::{crate_authors, crate_description, crate_name, Arg, Command as ClapApp};
-use simplelog::{Config, LevelFilter, WriteLogger};
-use std::{
-    env,
-    fs::{self, File},
-    path::PathBuf,
-};
+use std::{fs, path::PathBuf};
+
+use crate::db::{connection::Connection, connection_url::parse_connection_url};
 
 pub struct CliArgs {
     pub theme: PathBuf,
+    /// Open every connection read-only, regardless of its own setting.
+    pub read_only: bool,
+    pub start: Option<StartConnection>,
+}
+
+/// What to connect to right after startup, skipping the Types and Connections tabs.
+#[derive(Debug)]
+pub enum StartConnection {
+    /// From `--url` or `DATABASE_URL`. It is not added to the saved connections.
+    Url(Box<Connection>),
+    /// `--connection`, the name of a saved connection.
+    Saved(String),
 }
 
 pub fn process_cmdline() -> Result<CliArgs> {
@@ -20,18 +29,34 @@ pub fn process_cmdline() -> Result<CliArgs> {
         setup_logging()?;
     }
 
-    let workdir = arg_matches.get_one::<String>("workdir").map(PathBuf::from);
-    let gitdir = arg_matches
-        .get_one::<String>("directory")
-        .map_or_else(|| PathBuf::from("."), PathBuf::from);
-
     let arg_theme = arg_matches
         .get_one::<String>("theme")
         .map_or_else(|| PathBuf::from("theme.ron"), PathBuf::from);
 
     let theme = get_app_config_path()?.join(arg_theme);
 
-    Ok(CliArgs { theme })
+    let read_only = arg_matches.get_flag("read-only");
+
+    // an explicit saved connection wins over a DATABASE_URL in the environment
+    let start = match (
+        arg_matches.get_one::<String>("connection"),
+        arg_matches.get_one::<String>("url"),
+    ) {
+        (Some(name), _) => Some(StartConnection::Saved(name.clone())),
+        (None, Some(url)) => {
+            let mut connection =
+                parse_connection_url(url).context("invalid --url or DATABASE_URL")?;
+            connection.name = connection.summary();
+            Some(StartConnection::Url(Box::new(connection)))
+        }
+        (None, None) => None,
+    };
+
+    Ok(CliArgs {
+        theme,
+        read_only,
+        start,
+    })
 }
 
 fn app() -> ClapApp {
@@ -45,6 +70,39 @@ fn app() -> ClapApp {
                 .long("logging")
                 .num_args(0),
         )
+        .arg(
+            Arg::new("theme")
+                .help("Set color theme filename loaded from config directory")
+                .short('t')
+                .long("theme")
+                .value_name("THEME_FILE")
+                .default_value("theme.ron")
+                .num_args(1),
+        )
+        .arg(
+            Arg::new("read-only")
+                .help("Open all connections in read-only mode")
+                .long("read-only")
+                .num_args(0),
+        )
+        .arg(
+            Arg::new("url")
+                .help(
+                    "Connect to this postgres://, mysql://, mariadb:// or sqlite:// URL on startup",
+                )
+                .long("url")
+                .value_name("URL")
+                .env("DATABASE_URL")
+                .hide_env_values(true)
+                .num_args(1),
+        )
+        .arg(
+            Arg::new("connection")
+                .help("Connect to the saved connection with this name on startup")
+                .long("connection")
+                .value_name("NAME")
+                .num_args(1),
+        )
 }
 
 fn setup_logging() -> Result<()> {
@@ -53,7 +111,8 @@ fn setup_logging() -> Result<()> {
 
     println!("Logging enabled. log written to: {path:?}");
 
-    WriteLogger::init(LevelFilter::Trace, Config::default(), File::create(path)?)?;
+    // the tui logger owns the global logger, it also writes to the file
+    tui_logger::set_log_file(&path.to_string_lossy())?;
 
     Ok(())
 }
@@ -61,7 +120,7 @@ fn setup_logging() -> Result<()> {
 fn get_app_cache_path() -> Result<PathBuf> {
     let mut path = dirs::cache_dir().ok_or_else(|| anyhow!("failed to find os cache dir."))?;
 
-    path.push("gitui");
+    path.push("db-manager");
     fs::create_dir_all(&path)?;
     Ok(path)
 }
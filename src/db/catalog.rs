@@ -0,0 +1,759 @@
+use std::{cell::RefCell, collections::HashMap, fs, rc::Rc};
+
+use anyhow::Result;
+use sqlx::Row;
+
+use super::{
+    pool::{DbPool, QueryResult},
+    table_keys::TableKeys,
+};
+
+/// Rows shown when a table is opened.
+pub const DATA_ROW_LIMIT: usize = 200;
+
+/// What has been read from the servers of the open sessions, shared with the
+/// Databases and Tables tabs.
+pub type SharedCatalog = Rc<RefCell<Catalog>>;
+
+/// Data of a query running in the background.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub enum Loadable<T> {
+    #[default]
+    Loading,
+    Loaded(T),
+    Failed(String),
+}
+
+impl<T> Loadable<T> {
+    pub fn from_result(result: Result<T, String>) -> Self {
+        match result {
+            Ok(value) => Loadable::Loaded(value),
+            Err(error) => Loadable::Failed(error),
+        }
+    }
+
+    pub fn map<U>(self, f: impl FnOnce(T) -> U) -> Loadable<U> {
+        match self {
+            Loadable::Loading => Loadable::Loading,
+            Loadable::Loaded(value) => Loadable::Loaded(f(value)),
+            Loadable::Failed(error) => Loadable::Failed(error),
+        }
+    }
+}
+
+/// One database (or schema file for SQLite) on the server.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DatabaseInfo {
+    pub name: String,
+    /// On-disk size in bytes, `None` when the user may not see it.
+    pub size: Option<i64>,
+    /// Postgres only.
+    pub owner: Option<String>,
+    /// The database the pool is connected to (`main` for SQLite).
+    pub current: bool,
+}
+
+/// A schema of a Postgres database.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SchemaInfo {
+    pub name: String,
+    pub owner: String,
+    /// Tables, views and materialized views.
+    pub tables: i64,
+    /// Of its tables and materialized views including indexes.
+    pub size: Option<i64>,
+    /// First schema on the search path, where unqualified names are created.
+    pub current: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum TableKind {
+    Table,
+    View,
+    /// Postgres only.
+    MaterializedView,
+}
+
+impl TableKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TableKind::Table => "table",
+            TableKind::View => "view",
+            TableKind::MaterializedView => "materialized view",
+        }
+    }
+}
+
+/// A table or view of the selected database.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TableInfo {
+    /// Postgres only, MySQL and SQLite tables are addressed through their database.
+    pub schema: Option<String>,
+    pub name: String,
+    pub kind: TableKind,
+    /// Estimate from the planner statistics (exact for SQLite), `None` for
+    /// views and tables that were never analyzed.
+    pub rows: Option<i64>,
+    /// On-disk size in bytes including indexes, `None` for views.
+    pub size: Option<i64>,
+}
+
+/// A column of a table or view, as declared.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ColumnInfo {
+    pub name: String,
+    /// With length and precision, e.g. `character varying(64)` or `decimal(10,2)`.
+    pub data_type: String,
+    pub nullable: bool,
+    pub default: Option<String>,
+    /// Identity, auto increment or generated column, with its expression
+    /// where the catalog has it.
+    pub generated: Option<String>,
+    /// Only when it differs from the type's default collation (Postgres).
+    pub collation: Option<String>,
+    pub comment: Option<String>,
+}
+
+/// The table opened with Enter in the Tables tab.
+#[derive(Debug, Clone)]
+pub struct OpenTable {
+    pub table: TableInfo,
+    /// The first [`DATA_ROW_LIMIT`] rows.
+    pub data: Loadable<QueryResult>,
+    pub columns: Loadable<Vec<ColumnInfo>>,
+    pub keys: Loadable<TableKeys>,
+}
+
+/// The catalog of one session.
+#[derive(Debug, Clone, Default)]
+pub struct SessionCatalog {
+    pub databases: Loadable<Vec<DatabaseInfo>>,
+    /// Picked with Enter in the Databases tab, shown in the Tables tab.
+    pub selected_database: Option<String>,
+    /// Whether there is a schema level between databases and tables, only
+    /// for Postgres.
+    pub has_schemas: bool,
+    /// Schemas of the selected database.
+    pub schemas: Loadable<Vec<SchemaInfo>>,
+    /// Picked with Enter in the Schemas tab.
+    pub selected_schema: Option<String>,
+    /// Tables of the selected database (and schema).
+    pub tables: Loadable<Vec<TableInfo>>,
+    pub open_table: Option<OpenTable>,
+}
+
+#[derive(Debug, Default)]
+pub struct Catalog {
+    /// Session whose catalog the tabs show.
+    pub active_session: Option<u32>,
+    sessions: HashMap<u32, SessionCatalog>,
+}
+
+impl Catalog {
+    pub fn active(&self) -> Option<&SessionCatalog> {
+        self.sessions.get(&self.active_session?)
+    }
+
+    /// The catalog of `session`, created empty on first use.
+    pub fn session_mut(&mut self, session: u32) -> &mut SessionCatalog {
+        self.sessions.entry(session).or_default()
+    }
+
+    /// Drops the catalogs of sessions that are no longer open.
+    pub fn retain_sessions(&mut self, open: &[u32]) {
+        self.sessions.retain(|id, _| open.contains(id));
+    }
+}
+
+/// Lists the databases the server has: `pg_database` for Postgres, the
+/// schemata behind `SHOW DATABASES` for MySQL and MariaDB, and the attached
+/// database files of `PRAGMA database_list` for SQLite.
+pub async fn list_databases(pool: &DbPool) -> Result<Vec<DatabaseInfo>> {
+    let databases = match pool {
+        DbPool::Postgres(pool) => {
+            // the size needs CONNECT privilege, pg_database_size fails without it
+            sqlx::query(
+                "SELECT d.datname::text AS name,
+                        pg_catalog.pg_get_userbyid(d.datdba)::text AS owner,
+                        CASE WHEN pg_catalog.has_database_privilege(d.datname, 'CONNECT')
+                             THEN pg_catalog.pg_database_size(d.datname) END AS size,
+                        d.datname = pg_catalog.current_database() AS current
+                 FROM pg_catalog.pg_database d
+                 WHERE NOT d.datistemplate
+                 ORDER BY d.datname",
+            )
+            .fetch_all(pool)
+            .await?
+            .into_iter()
+            .map(|row| {
+                Ok(DatabaseInfo {
+                    name: row.try_get("name")?,
+                    size: row.try_get("size")?,
+                    owner: row.try_get("owner")?,
+                    current: row.try_get("current")?,
+                })
+            })
+            .collect::<Result<_>>()?
+        }
+        DbPool::MySql(pool) => {
+            let current: Option<String> = sqlx::query_scalar("SELECT DATABASE()")
+                .fetch_one(pool)
+                .await?;
+            // information_schema columns come back as binary strings on some
+            // servers, hence the casts
+            sqlx::query(
+                "SELECT CAST(s.schema_name AS CHAR) AS name,
+                        CAST(SUM(t.data_length + t.index_length) AS SIGNED) AS size
+                 FROM information_schema.schemata s
+                 LEFT JOIN information_schema.tables t ON t.table_schema = s.schema_name
+                 GROUP BY s.schema_name
+                 ORDER BY s.schema_name",
+            )
+            .fetch_all(pool)
+            .await?
+            .into_iter()
+            .map(|row| {
+                let name: String = row.try_get("name")?;
+                Ok(DatabaseInfo {
+                    current: current.as_deref() == Some(name.as_str()),
+                    name,
+                    size: row.try_get("size")?,
+                    owner: None,
+                })
+            })
+            .collect::<Result<_>>()?
+        }
+        DbPool::Sqlite(pool) => sqlx::query("PRAGMA database_list")
+            .fetch_all(pool)
+            .await?
+            .into_iter()
+            .map(|row| {
+                let name: String = row.try_get("name")?;
+                let file: String = row.try_get("file")?;
+                Ok(DatabaseInfo {
+                    current: name == "main",
+                    name,
+                    // in-memory and temp databases have no file
+                    size: fs::metadata(&file).ok().map(|meta| meta.len() as i64),
+                    owner: None,
+                })
+            })
+            .collect::<Result<_>>()?,
+    };
+    Ok(databases)
+}
+
+/// Lists the schemas of the Postgres database `pool` is connected to,
+/// without the system schemas. Empty for MySQL and SQLite.
+pub async fn list_schemas(pool: &DbPool) -> Result<Vec<SchemaInfo>> {
+    let DbPool::Postgres(pool) = pool else {
+        return Ok(vec![]);
+    };
+    sqlx::query(
+        "SELECT n.nspname::text AS name,
+                pg_catalog.pg_get_userbyid(n.nspowner)::text AS owner,
+                count(c.oid) AS tables,
+                sum(pg_catalog.pg_total_relation_size(c.oid))
+                    FILTER (WHERE c.relkind <> 'v')::bigint AS size,
+                n.nspname = current_schema() AS current
+         FROM pg_catalog.pg_namespace n
+         LEFT JOIN pg_catalog.pg_class c
+           ON c.relnamespace = n.oid
+          AND c.relkind IN ('r', 'p', 'v', 'm')
+          AND NOT c.relispartition
+         WHERE n.nspname NOT IN ('pg_catalog', 'information_schema')
+           AND n.nspname NOT LIKE 'pg\\_toast%'
+           AND n.nspname NOT LIKE 'pg\\_temp%'
+         GROUP BY n.oid, n.nspname, n.nspowner
+         ORDER BY n.nspname",
+    )
+    .fetch_all(pool)
+    .await?
+    .into_iter()
+    .map(|row| {
+        Ok(SchemaInfo {
+            name: row.try_get("name")?,
+            owner: row.try_get("owner")?,
+            tables: row.try_get("tables")?,
+            size: row.try_get("size")?,
+            current: row.try_get("current")?,
+        })
+    })
+    .collect()
+}
+
+/// Lists the tables, views and materialized views of `database` with their
+/// estimated row count and size. For Postgres `pool` must be connected to
+/// `database`, and only the tables of `schema` are listed when given. System
+/// schemas are left out.
+pub async fn list_tables(
+    pool: &DbPool,
+    database: &str,
+    schema: Option<&str>,
+) -> Result<Vec<TableInfo>> {
+    let tables = match pool {
+        DbPool::Postgres(pool) => sqlx::query(
+            "SELECT n.nspname::text AS schema,
+                    c.relname::text AS name,
+                    c.relkind::text AS kind,
+                    CASE WHEN c.relkind <> 'v' AND c.reltuples >= 0
+                         THEN c.reltuples::bigint END AS rows,
+                    CASE WHEN c.relkind <> 'v'
+                         THEN pg_catalog.pg_total_relation_size(c.oid) END AS size
+             FROM pg_catalog.pg_class c
+             JOIN pg_catalog.pg_namespace n ON n.oid = c.relnamespace
+             WHERE c.relkind IN ('r', 'p', 'v', 'm')
+               AND NOT c.relispartition
+               AND n.nspname NOT IN ('pg_catalog', 'information_schema')
+               AND n.nspname NOT LIKE 'pg\\_toast%'
+               AND n.nspname NOT LIKE 'pg\\_temp%'
+               AND ($1::text IS NULL OR n.nspname = $1)
+             ORDER BY n.nspname, c.relname",
+        )
+        .bind(schema)
+        .fetch_all(pool)
+        .await?
+        .into_iter()
+        .map(|row| {
+            let kind: String = row.try_get("kind")?;
+            Ok(TableInfo {
+                schema: row.try_get("schema")?,
+                name: row.try_get("name")?,
+                kind: match kind.as_str() {
+                    "v" => TableKind::View,
+                    "m" => TableKind::MaterializedView,
+                    _ => TableKind::Table,
+                },
+                rows: row.try_get("rows")?,
+                size: row.try_get("size")?,
+            })
+        })
+        .collect::<Result<_>>()?,
+        DbPool::MySql(pool) => sqlx::query(
+            "SELECT CAST(table_name AS CHAR) AS name,
+                    CAST(table_type AS CHAR) AS kind,
+                    CAST(table_rows AS SIGNED) AS `rows`,
+                    CAST(data_length + index_length AS SIGNED) AS size
+             FROM information_schema.tables
+             WHERE table_schema = ?
+             ORDER BY table_name",
+        )
+        .bind(database)
+        .fetch_all(pool)
+        .await?
+        .into_iter()
+        .map(|row| {
+            let kind: String = row.try_get("kind")?;
+            Ok(TableInfo {
+                schema: None,
+                name: row.try_get("name")?,
+                kind: if kind.contains("VIEW") {
+                    TableKind::View
+                } else {
+                    TableKind::Table
+                },
+                rows: row.try_get("rows")?,
+                size: row.try_get("size")?,
+            })
+        })
+        .collect::<Result<_>>()?,
+        DbPool::Sqlite(pool) => {
+            let schema = quote_identifier(database);
+            let entries: Vec<(String, String)> = sqlx::query_as(&format!(
+                "SELECT name, type FROM {}.sqlite_master
+                 WHERE type IN ('table', 'view') AND name NOT LIKE 'sqlite\\_%' ESCAPE '\\'
+                 ORDER BY name",
+                schema
+            ))
+            .fetch_all(pool)
+            .await?;
+            // dbstat is compiled into the bundled SQLite, sizes add up the
+            // pages of the table and its indexes
+            let sizes: HashMap<String, i64> = sqlx::query_as(&format!(
+                "SELECT m.tbl_name, SUM(s.pgsize) FROM dbstat s
+                 JOIN {}.sqlite_master m ON m.name = s.name
+                 WHERE s.schema = ?
+                 GROUP BY m.tbl_name",
+                schema
+            ))
+            .bind(database)
+            .fetch_all(pool)
+            .await
+            .map(|sizes| sizes.into_iter().collect())
+            .unwrap_or_default();
+
+            // row counts estimated by the last ANALYZE, sqlite_stat1 only exists
+            // after one has run. The first number of a stat is the number of
+            // rows, partial indexes count fewer so the largest is taken
+            let mut estimates: HashMap<String, i64> = HashMap::new();
+            let stats: Vec<(String, String)> =
+                sqlx::query_as(&format!("SELECT tbl, stat FROM {}.sqlite_stat1", schema))
+                    .fetch_all(pool)
+                    .await
+                    .unwrap_or_default();
+            for (table, stat) in stats {
+                if let Some(rows) = stat
+                    .split_whitespace()
+                    .next()
+                    .and_then(|rows| rows.parse::<i64>().ok())
+                {
+                    let estimate = estimates.entry(table).or_default();
+                    *estimate = (*estimate).max(rows);
+                }
+            }
+
+            entries
+                .into_iter()
+                .map(|(name, kind)| TableInfo {
+                    schema: None,
+                    kind: if kind == "view" {
+                        TableKind::View
+                    } else {
+                        TableKind::Table
+                    },
+                    rows: estimates.get(&name).copied(),
+                    size: sizes.get(&name).copied(),
+                    name,
+                })
+                .collect()
+        }
+    };
+    Ok(tables)
+}
+
+/// Fetches the first [`DATA_ROW_LIMIT`] rows of `table` in `database`.
+pub async fn table_data(pool: &DbPool, database: &str, table: &TableInfo) -> Result<QueryResult> {
+    pool.fetch(&format!(
+        "SELECT * FROM {} LIMIT {}",
+        qualified_name(pool, database, table),
+        DATA_ROW_LIMIT
+    ))
+    .await
+}
+
+/// Reads the columns of `table` in `database` from `pg_attribute`,
+/// `information_schema.columns` or `PRAGMA table_xinfo`.
+pub async fn table_columns(
+    pool: &DbPool,
+    database: &str,
+    table: &TableInfo,
+) -> Result<Vec<ColumnInfo>> {
+    let columns = match pool {
+        DbPool::Postgres(pg_pool) => sqlx::query(
+            "SELECT a.attname::text AS name,
+                    pg_catalog.format_type(a.atttypid, a.atttypmod) AS data_type,
+                    NOT a.attnotnull AS nullable,
+                    CASE WHEN a.attgenerated = ''
+                         THEN pg_catalog.pg_get_expr(d.adbin, d.adrelid) END AS default_value,
+                    CASE WHEN a.attidentity = 'a' THEN 'identity always'
+                         WHEN a.attidentity = 'd' THEN 'identity by default'
+                         WHEN a.attgenerated = 's'
+                         THEN 'stored: ' || pg_catalog.pg_get_expr(d.adbin, d.adrelid)
+                    END AS generated,
+                    CASE WHEN co.collname <> 'default' THEN co.collname::text END AS collation,
+                    pg_catalog.col_description(a.attrelid, a.attnum) AS comment
+             FROM pg_catalog.pg_attribute a
+             LEFT JOIN pg_catalog.pg_attrdef d ON d.adrelid = a.attrelid AND d.adnum = a.attnum
+             LEFT JOIN pg_catalog.pg_collation co ON co.oid = a.attcollation
+             WHERE a.attrelid = $1::regclass AND a.attnum > 0 AND NOT a.attisdropped
+             ORDER BY a.attnum",
+        )
+        .bind(qualified_name(pool, database, table))
+        .fetch_all(pg_pool)
+        .await?
+        .into_iter()
+        .map(|row| {
+            Ok(ColumnInfo {
+                name: row.try_get("name")?,
+                data_type: row.try_get("data_type")?,
+                nullable: row.try_get("nullable")?,
+                default: row.try_get("default_value")?,
+                generated: row.try_get("generated")?,
+                collation: row.try_get("collation")?,
+                comment: row.try_get("comment")?,
+            })
+        })
+        .collect::<Result<_>>()?,
+        DbPool::MySql(pool) => sqlx::query(
+            "SELECT CAST(column_name AS CHAR) AS name,
+                    CAST(column_type AS CHAR) AS data_type,
+                    CAST(is_nullable = 'YES' AS SIGNED) AS nullable,
+                    CAST(column_default AS CHAR) AS default_value,
+                    CAST(extra AS CHAR) AS extra,
+                    CAST(generation_expression AS CHAR) AS generation_expression,
+                    CAST(collation_name AS CHAR) AS collation,
+                    CAST(column_comment AS CHAR) AS comment
+             FROM information_schema.columns
+             WHERE table_schema = ? AND table_name = ?
+             ORDER BY ordinal_position",
+        )
+        .bind(database)
+        .bind(&table.name)
+        .fetch_all(pool)
+        .await?
+        .into_iter()
+        .map(|row| {
+            let nullable: i64 = row.try_get("nullable")?;
+            let default: Option<String> = row.try_get("default_value")?;
+            let extra: String = row
+                .try_get::<Option<String>, _>("extra")?
+                .unwrap_or_default();
+            let expression: Option<String> = row.try_get("generation_expression")?;
+            let comment: String = row
+                .try_get::<Option<String>, _>("comment")?
+                .unwrap_or_default();
+            Ok(ColumnInfo {
+                name: row.try_get("name")?,
+                data_type: row.try_get("data_type")?,
+                nullable: nullable != 0,
+                // MariaDB reports a missing default as the string NULL
+                default: default.filter(|default| default != "NULL"),
+                generated: mysql_generated(&extra, expression.as_deref()),
+                collation: row.try_get("collation")?,
+                comment: Some(comment).filter(|comment| !comment.is_empty()),
+            })
+        })
+        .collect::<Result<_>>()?,
+        DbPool::Sqlite(pool) => sqlx::query(
+            "SELECT name, type, \"notnull\", dflt_value, hidden
+             FROM pragma_table_xinfo(?, ?)
+             ORDER BY cid",
+        )
+        .bind(&table.name)
+        .bind(database)
+        .fetch_all(pool)
+        .await?
+        .into_iter()
+        .map(|row| {
+            let not_null: bool = row.try_get("notnull")?;
+            let hidden: i64 = row.try_get("hidden")?;
+            Ok(ColumnInfo {
+                name: row.try_get("name")?,
+                data_type: row.try_get("type")?,
+                nullable: !not_null,
+                default: row.try_get("dflt_value")?,
+                // the expression is only in the CREATE TABLE statement
+                generated: match hidden {
+                    2 => Some("virtual".to_string()),
+                    3 => Some("stored".to_string()),
+                    _ => None,
+                },
+                collation: None,
+                comment: None,
+            })
+        })
+        .collect::<Result<_>>()?,
+    };
+    Ok(columns)
+}
+
+/// What `EXTRA` and `GENERATION_EXPRESSION` of a MySQL column say about how
+/// its value is produced.
+fn mysql_generated(extra: &str, expression: Option<&str>) -> Option<String> {
+    let extra = extra.to_lowercase();
+    let expression = expression.filter(|expression| !expression.is_empty());
+    if extra.contains("auto_increment") {
+        Some("auto_increment".to_string())
+    } else if let (Some(expression), true) = (expression, extra.contains("generated")) {
+        let kind = if extra.contains("stored") || extra.contains("persistent") {
+            "stored"
+        } else {
+            "virtual"
+        };
+        Some(format!("{}: {}", kind, expression))
+    } else {
+        None
+    }
+}
+
+/// `table` with its schema (Postgres) or database, quoted for the driver.
+pub fn qualified_name(pool: &DbPool, database: &str, table: &TableInfo) -> String {
+    let quote = match pool {
+        DbPool::MySql(_) => quote_mysql_identifier,
+        DbPool::Postgres(_) | DbPool::Sqlite(_) => quote_identifier,
+    };
+    format!(
+        "{}.{}",
+        quote(table.schema.as_deref().unwrap_or(database)),
+        quote(&table.name)
+    )
+}
+
+/// `name` as a standard SQL identifier: `"name"`.
+pub fn quote_identifier(name: &str) -> String {
+    format!("\"{}\"", name.replace('"', "\"\""))
+}
+
+/// `name` as a MySQL identifier: `` `name` ``, works without `ANSI_QUOTES`.
+fn quote_mysql_identifier(name: &str) -> String {
+    format!("`{}`", name.replace('`', "``"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{db::connection::Connection, tabs::db_types_tab::DBTypes};
+    use pretty_assertions::assert_eq;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_list_sqlite_databases() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("app.db");
+        let connection = Connection {
+            db_type: DBTypes::SQLITE,
+            database: path.to_string_lossy().into_owned(),
+            create_if_missing: true,
+            ..Default::default()
+        };
+        let pool = DbPool::connect(&connection, false).await.unwrap();
+        pool.execute("CREATE TABLE t (id INTEGER)").await.unwrap();
+
+        let databases = list_databases(&pool).await.unwrap();
+        assert_eq!(databases.len(), 1);
+        assert_eq!(databases[0].name, "main");
+        assert!(databases[0].current);
+        assert!(databases[0].size.unwrap() > 0);
+        pool.close().await;
+    }
+
+    #[tokio::test]
+    async fn test_list_sqlite_tables_and_data() {
+        let dir = TempDir::new().unwrap();
+        let connection = Connection {
+            db_type: DBTypes::SQLITE,
+            database: dir.path().join("app.db").to_string_lossy().into_owned(),
+            create_if_missing: true,
+            ..Default::default()
+        };
+        let pool = DbPool::connect(&connection, false).await.unwrap();
+        pool.execute(
+            "CREATE TABLE \"order items\" (id INTEGER PRIMARY KEY, note TEXT, data BLOB);
+             INSERT INTO \"order items\" VALUES (1, 'first', x'00ff'), (2, NULL, NULL);
+             CREATE INDEX items_note ON \"order items\" (note);
+             CREATE VIEW notes AS SELECT note FROM \"order items\";",
+        )
+        .await
+        .unwrap();
+
+        let tables = list_tables(&pool, "main", None).await.unwrap();
+        assert_eq!(tables.len(), 2);
+        assert_eq!(tables[0].name, "notes");
+        assert_eq!(tables[0].kind, TableKind::View);
+        assert_eq!(tables[0].rows, None);
+        assert_eq!(tables[1].kind, TableKind::Table);
+        // no estimate until the statistics have been gathered
+        assert_eq!(tables[1].rows, None);
+        assert!(tables[1].size.unwrap() > 0);
+
+        pool.execute("ANALYZE").await.unwrap();
+        let tables = list_tables(&pool, "main", None).await.unwrap();
+        assert_eq!(tables[0].rows, None);
+        assert_eq!(tables[1].rows, Some(2));
+
+        let data = table_data(&pool, "main", &tables[1]).await.unwrap();
+        assert_eq!(data.columns, vec!["id", "note", "data"]);
+        assert_eq!(
+            data.rows,
+            vec![
+                vec![
+                    Some("1".to_string()),
+                    Some("first".to_string()),
+                    Some("\\x00ff".to_string())
+                ],
+                vec![Some("2".to_string()), None, None],
+            ]
+        );
+        pool.close().await;
+    }
+
+    #[tokio::test]
+    async fn test_sqlite_table_columns() {
+        let connection = Connection {
+            db_type: DBTypes::SQLITE,
+            database: ":memory:".to_string(),
+            ..Default::default()
+        };
+        let pool = DbPool::connect(&connection, false).await.unwrap();
+        pool.execute(
+            "CREATE TABLE items (
+                 id INTEGER PRIMARY KEY,
+                 price NUMERIC(10, 2) NOT NULL DEFAULT 0,
+                 total NUMERIC GENERATED ALWAYS AS (price * 2) STORED
+             )",
+        )
+        .await
+        .unwrap();
+        let table = TableInfo {
+            schema: None,
+            name: "items".to_string(),
+            kind: TableKind::Table,
+            rows: None,
+            size: None,
+        };
+
+        let columns = table_columns(&pool, "main", &table).await.unwrap();
+        assert_eq!(
+            columns,
+            vec![
+                ColumnInfo {
+                    name: "id".to_string(),
+                    data_type: "INTEGER".to_string(),
+                    nullable: true,
+                    ..Default::default()
+                },
+                ColumnInfo {
+                    name: "price".to_string(),
+                    data_type: "NUMERIC(10, 2)".to_string(),
+                    nullable: false,
+                    default: Some("0".to_string()),
+                    ..Default::default()
+                },
+                ColumnInfo {
+                    name: "total".to_string(),
+                    data_type: "NUMERIC".to_string(),
+                    nullable: true,
+                    generated: Some("stored".to_string()),
+                    ..Default::default()
+                },
+            ]
+        );
+        pool.close().await;
+    }
+
+    #[test]
+    fn test_mysql_generated() {
+        assert_eq!(
+            mysql_generated("auto_increment", None).as_deref(),
+            Some("auto_increment")
+        );
+        assert_eq!(
+            mysql_generated("STORED GENERATED", Some("(`price` * 2)")).as_deref(),
+            Some("stored: (`price` * 2)")
+        );
+        assert_eq!(
+            mysql_generated("VIRTUAL GENERATED", Some("lower(`name`)")).as_deref(),
+            Some("virtual: lower(`name`)")
+        );
+        // a default of CURRENT_TIMESTAMP is not a generated column
+        assert_eq!(mysql_generated("DEFAULT_GENERATED", Some("")), None);
+    }
+
+    #[test]
+    fn test_catalog_follows_sessions() {
+        let mut catalog = Catalog::default();
+        catalog.session_mut(1).selected_database = Some("orders".to_string());
+        catalog.session_mut(2);
+        assert!(catalog.active().is_none());
+
+        catalog.active_session = Some(1);
+        assert_eq!(
+            catalog.active().unwrap().selected_database.as_deref(),
+            Some("orders")
+        );
+
+        catalog.retain_sessions(&[2]);
+        assert!(catalog.active().is_none());
+        assert_eq!(catalog.session_mut(2).databases, Loadable::Loading);
+    }
+}
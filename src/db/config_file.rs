@@ -0,0 +1,84 @@
+use std::{
+    fmt, fs,
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use log::error;
+
+/// A config file written by a newer build. It is left untouched so going back
+/// to an older version never loses what the newer one saved.
+#[derive(Debug)]
+pub struct NewerVersion {
+    pub path: PathBuf,
+    pub version: u32,
+    pub supported: u32,
+}
+
+impl fmt::Display for NewerVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} was written by a newer version (format v{}, supported v{})",
+            self.path.display(),
+            self.version,
+            self.supported
+        )
+    }
+}
+
+impl std::error::Error for NewerVersion {}
+
+/// Moves an unparsable config file out of the way, to e.g.
+/// `connections.ron.1760000000.bak`, and returns where it went. Earlier
+/// backups are never overwritten.
+pub fn backup_broken_file(path: &Path) -> Option<PathBuf> {
+    if !path.exists() {
+        return None;
+    }
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs());
+    let file_name = path.file_name()?.to_string_lossy().into_owned();
+    let backup = (0..)
+        .map(|n| match n {
+            0 => path.with_file_name(format!("{}.{}.bak", file_name, timestamp)),
+            n => path.with_file_name(format!("{}.{}-{}.bak", file_name, timestamp, n)),
+        })
+        .find(|backup| !backup.exists())?;
+
+    match fs::rename(path, &backup) {
+        Ok(()) => {
+            error!("unreadable {} moved to {:?}", file_name, backup);
+            Some(backup)
+        }
+        Err(e) => {
+            error!("failed to back up {:?}: {:?}", path, e);
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_backups_do_not_overwrite_each_other() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("connections.ron");
+
+        fs::write(&path, "first").unwrap();
+        let first = backup_broken_file(&path).unwrap();
+        fs::write(&path, "second").unwrap();
+        let second = backup_broken_file(&path).unwrap();
+
+        assert!(!path.exists());
+        assert_ne!(first, second);
+        assert_eq!(fs::read_to_string(first).unwrap(), "first");
+        assert_eq!(fs::read_to_string(second).unwrap(), "second");
+        assert_eq!(backup_broken_file(&path), None);
+    }
+}
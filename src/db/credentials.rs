@@ -0,0 +1,385 @@
+use std::{collections::BTreeMap, fs, path::PathBuf};
+
+use anyhow::{anyhow, Context, Result};
+use argon2::Argon2;
+use chacha20poly1305::{
+    aead::{rand_core::RngCore, Aead, AeadCore, KeyInit, OsRng, Payload},
+    ChaCha20Poly1305, Key, Nonce,
+};
+use log::{debug, error};
+use ron::ser::{to_string_pretty, PrettyConfig};
+use serde::{Deserialize, Serialize};
+
+use super::config_file::{backup_broken_file, NewerVersion};
+use crate::args::get_app_config_path;
+
+const CREDENTIALS_FILE: &str = "credentials.ron";
+const CURRENT_VERSION: u32 = 1;
+const SALT_LEN: usize = 16;
+
+/// Known plaintext encrypted with the master key, used to tell a wrong
+/// passphrase apart from a corrupted secret.
+const CHECK_PLAINTEXT: &[u8] = b"db-manager credentials";
+const CHECK_AAD: &[u8] = b"check";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Sealed {
+    nonce: Vec<u8>,
+    ciphertext: Vec<u8>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct CredentialsFile {
+    version: u32,
+    salt: Vec<u8>,
+    check: Sealed,
+    #[serde(default)]
+    secrets: BTreeMap<u32, Sealed>,
+}
+
+/// Connection passwords encrypted at rest with a key derived from a master
+/// passphrase (Argon2id + ChaCha20-Poly1305). Each secret is bound to its
+/// connection id so entries can not be swapped around in the file.
+#[derive(Default)]
+pub struct CredentialStore {
+    path: PathBuf,
+    file: Option<CredentialsFile>,
+    cipher: Option<ChaCha20Poly1305>,
+    /// Why the file must not be written, e.g. it comes from a newer version.
+    save_blocked: Option<String>,
+}
+
+impl CredentialStore {
+    /// Loads the store from the default config location. An unparsable file
+    /// is moved to a backup. A file from a newer version, one that can not be
+    /// read or one the backup fails for is left alone, and no new store is
+    /// set up over it.
+    pub fn load_default() -> Self {
+        let path = match get_app_config_path() {
+            Ok(dir) => dir.join(CREDENTIALS_FILE),
+            Err(e) => {
+                error!("failed to locate config dir: {:?}", e);
+                return Self::default();
+            }
+        };
+
+        Self::load(path.clone()).unwrap_or_else(|e| Self::load_failed(path, e))
+    }
+
+    fn load_failed(path: PathBuf, e: anyhow::Error) -> Self {
+        error!("failed to load credential store: {:?}", e);
+        let save_blocked = if e.is::<ron::error::SpannedError>() {
+            // without a backup the broken file is the only copy left
+            match backup_broken_file(&path) {
+                Some(_) => None,
+                None => Some(format!("{:#}, and it could not be backed up", e)),
+            }
+        } else {
+            Some(format!("{:#}", e))
+        };
+        Self {
+            path,
+            save_blocked,
+            ..Default::default()
+        }
+    }
+
+    pub fn load(path: PathBuf) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self {
+                path,
+                ..Default::default()
+            });
+        }
+
+        let content = fs::read_to_string(&path)
+            .with_context(|| format!("failed to read {}", path.display()))?;
+        let file: CredentialsFile = ron::from_str(&content)
+            .with_context(|| format!("failed to parse {}", path.display()))?;
+        if file.version > CURRENT_VERSION {
+            return Err(NewerVersion {
+                path,
+                version: file.version,
+                supported: CURRENT_VERSION,
+            }
+            .into());
+        }
+
+        Ok(Self {
+            path,
+            file: Some(file),
+            cipher: None,
+            save_blocked: None,
+        })
+    }
+
+    /// Whether a master passphrase has been set up.
+    pub fn is_initialized(&self) -> bool {
+        self.file.is_some()
+    }
+
+    pub fn is_unlocked(&self) -> bool {
+        self.cipher.is_some()
+    }
+
+    /// Creates a new, empty store protected by `passphrase`.
+    pub fn initialize(&mut self, passphrase: &str) -> Result<()> {
+        if self.is_initialized() {
+            return Err(anyhow!("credential store already exists"));
+        }
+        if let Some(reason) = &self.save_blocked {
+            return Err(anyhow!("credential store can not be set up: {}", reason));
+        }
+
+        let mut salt = vec![0u8; SALT_LEN];
+        OsRng.fill_bytes(&mut salt);
+        let cipher = derive_cipher(passphrase, &salt)?;
+        let check = seal(&cipher, CHECK_PLAINTEXT, CHECK_AAD)?;
+
+        self.file = Some(CredentialsFile {
+            version: CURRENT_VERSION,
+            salt,
+            check,
+            secrets: BTreeMap::new(),
+        });
+        self.cipher = Some(cipher);
+        self.save()
+    }
+
+    pub fn unlock(&mut self, passphrase: &str) -> Result<()> {
+        let file = self
+            .file
+            .as_ref()
+            .ok_or_else(|| anyhow!("no credential store has been set up"))?;
+
+        let cipher = derive_cipher(passphrase, &file.salt)?;
+        match open(&cipher, &file.check, CHECK_AAD) {
+            Ok(check) if check == CHECK_PLAINTEXT => {
+                self.cipher = Some(cipher);
+                debug!("credential store unlocked");
+                Ok(())
+            }
+            _ => Err(anyhow!("wrong master passphrase")),
+        }
+    }
+
+    pub fn get_password(&self, id: u32) -> Result<Option<String>> {
+        let (file, cipher) = self.unlocked()?;
+        let Some(sealed) = file.secrets.get(&id) else {
+            return Ok(None);
+        };
+
+        let password = open(cipher, sealed, &id.to_le_bytes())?;
+        Ok(Some(String::from_utf8(password)?))
+    }
+
+    /// Whether a secret is stored for `id`; works while locked.
+    pub fn has_password(&self, id: u32) -> bool {
+        self.file
+            .as_ref()
+            .is_some_and(|file| file.secrets.contains_key(&id))
+    }
+
+    pub fn set_password(&mut self, id: u32, password: &str) -> Result<()> {
+        let (_, cipher) = self.unlocked()?;
+        let sealed = seal(cipher, password.as_bytes(), &id.to_le_bytes())?;
+        if let Some(file) = self.file.as_mut() {
+            file.secrets.insert(id, sealed);
+        }
+        self.save()
+    }
+
+    /// Removing a secret does not need the key, so it also works while locked.
+    pub fn remove_password(&mut self, id: u32) -> Result<()> {
+        let removed = self
+            .file
+            .as_mut()
+            .and_then(|file| file.secrets.remove(&id))
+            .is_some();
+        if removed {
+            self.save()?;
+        }
+        Ok(())
+    }
+
+    fn unlocked(&self) -> Result<(&CredentialsFile, &ChaCha20Poly1305)> {
+        match (&self.file, &self.cipher) {
+            (Some(file), Some(cipher)) => Ok((file, cipher)),
+            (None, _) => Err(anyhow!("no credential store has been set up")),
+            (Some(_), None) => Err(anyhow!("credential store is locked")),
+        }
+    }
+
+    fn save(&self) -> Result<()> {
+        let Some(file) = &self.file else {
+            return Ok(());
+        };
+        if let Some(reason) = &self.save_blocked {
+            return Err(anyhow!("credential store is not written: {}", reason));
+        }
+        if self.path.as_os_str().is_empty() {
+            return Err(anyhow!("credential store has no file path"));
+        }
+
+        let content = to_string_pretty(file, PrettyConfig::default())?;
+        let tmp_path = self.path.with_extension("ron.tmp");
+        fs::write(&tmp_path, content)
+            .with_context(|| format!("failed to write {}", tmp_path.display()))?;
+        restrict_permissions(&tmp_path);
+        fs::rename(&tmp_path, &self.path)
+            .with_context(|| format!("failed to write {}", self.path.display()))?;
+        Ok(())
+    }
+}
+
+// never print key material or ciphertexts
+impl std::fmt::Debug for CredentialStore {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CredentialStore")
+            .field("path", &self.path)
+            .field("initialized", &self.is_initialized())
+            .field("unlocked", &self.is_unlocked())
+            .finish()
+    }
+}
+
+fn derive_cipher(passphrase: &str, salt: &[u8]) -> Result<ChaCha20Poly1305> {
+    let mut key = [0u8; 32];
+    Argon2::default()
+        .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+        .map_err(|e| anyhow!("failed to derive key: {}", e))?;
+    let cipher = ChaCha20Poly1305::new(Key::from_slice(&key));
+    key.fill(0);
+    Ok(cipher)
+}
+
+fn seal(cipher: &ChaCha20Poly1305, plaintext: &[u8], aad: &[u8]) -> Result<Sealed> {
+    let nonce = ChaCha20Poly1305::generate_nonce(&mut OsRng);
+    let ciphertext = cipher
+        .encrypt(
+            &nonce,
+            Payload {
+                msg: plaintext,
+                aad,
+            },
+        )
+        .map_err(|_| anyhow!("failed to encrypt secret"))?;
+    Ok(Sealed {
+        nonce: nonce.to_vec(),
+        ciphertext,
+    })
+}
+
+fn open(cipher: &ChaCha20Poly1305, sealed: &Sealed, aad: &[u8]) -> Result<Vec<u8>> {
+    if sealed.nonce.len() != 12 {
+        return Err(anyhow!("corrupted secret"));
+    }
+    cipher
+        .decrypt(
+            Nonce::from_slice(&sealed.nonce),
+            Payload {
+                msg: &sealed.ciphertext,
+                aad,
+            },
+        )
+        .map_err(|_| anyhow!("failed to decrypt secret"))
+}
+
+#[cfg(unix)]
+fn restrict_permissions(path: &PathBuf) {
+    use std::os::unix::fs::PermissionsExt;
+    if let Err(e) = fs::set_permissions(path, fs::Permissions::from_mode(0o600)) {
+        error!("failed to restrict permissions of {:?}: {:?}", path, e);
+    }
+}
+
+#[cfg(not(unix))]
+fn restrict_permissions(_path: &PathBuf) {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_store_and_unlock() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join(CREDENTIALS_FILE);
+
+        let mut store = CredentialStore::load(path.clone()).unwrap();
+        assert!(!store.is_initialized());
+        store.initialize("correct horse").unwrap();
+        store.set_password(3, "hunter2").unwrap();
+
+        let content = fs::read_to_string(&path).unwrap();
+        assert!(!content.contains("hunter2"));
+
+        let mut store = CredentialStore::load(path).unwrap();
+        assert!(store.is_initialized());
+        assert!(store.get_password(3).is_err());
+        assert_eq!(
+            store.unlock("wrong").unwrap_err().to_string(),
+            "wrong master passphrase"
+        );
+
+        store.unlock("correct horse").unwrap();
+        assert_eq!(store.get_password(3).unwrap().as_deref(), Some("hunter2"));
+        assert_eq!(store.get_password(4).unwrap(), None);
+
+        store.remove_password(3).unwrap();
+        assert_eq!(store.get_password(3).unwrap(), None);
+    }
+
+    #[test]
+    fn test_secret_bound_to_connection_id() {
+        let dir = TempDir::new().unwrap();
+        let mut store = CredentialStore::load(dir.path().join(CREDENTIALS_FILE)).unwrap();
+        store.initialize("pass").unwrap();
+        store.set_password(1, "one").unwrap();
+
+        // moving a secret to another connection must not decrypt
+        let file = store.file.as_mut().unwrap();
+        let sealed = file.secrets.remove(&1).unwrap();
+        file.secrets.insert(2, sealed);
+        assert!(store.get_password(2).is_err());
+    }
+
+    #[test]
+    fn test_unreadable_file_is_never_overwritten() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join(CREDENTIALS_FILE);
+        let content = "(version: 99, salt: [], check: (nonce: [], ciphertext: []))";
+        fs::write(&path, content).unwrap();
+
+        let error = CredentialStore::load(path.clone()).unwrap_err();
+        let mut store = CredentialStore::load_failed(path.clone(), error);
+        assert!(!store.is_initialized());
+        assert!(store.initialize("pass").is_err());
+        assert!(!store.is_initialized());
+        assert_eq!(fs::read_to_string(&path).unwrap(), content);
+
+        // a broken file is kept as a backup before a new store replaces it
+        fs::write(&path, "(version: 1, salt: [").unwrap();
+        let error = CredentialStore::load(path.clone()).unwrap_err();
+        let mut store = CredentialStore::load_failed(path.clone(), error);
+        store.initialize("pass").unwrap();
+        let backups = fs::read_dir(dir.path())
+            .unwrap()
+            .filter(|entry| {
+                let name = entry.as_ref().unwrap().file_name();
+                name.to_string_lossy().ends_with(".bak")
+            })
+            .count();
+        assert_eq!(backups, 1);
+
+        // no new store either when the broken file could not be backed up
+        fs::write(&path, "(version: 1, salt: [").unwrap();
+        let error = CredentialStore::load(path.clone()).unwrap_err();
+        fs::create_dir(dir.path().join("moved")).unwrap();
+        let missing = dir.path().join("moved").join(CREDENTIALS_FILE);
+        let mut store = CredentialStore::load_failed(missing.clone(), error);
+        assert!(store.initialize("pass").is_err());
+        assert!(!missing.exists());
+    }
+}
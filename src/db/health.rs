@@ -0,0 +1,205 @@
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    rc::Rc,
+    time::{Duration, Instant},
+};
+
+/// How often an idle, healthy session is pinged.
+pub const CHECK_INTERVAL: Duration = Duration::from_secs(30);
+/// A ping slower than this marks the session as degraded.
+pub const SLOW_PING: Duration = Duration::from_secs(1);
+/// How long a ping may take before it counts as failed.
+pub const CHECK_TIMEOUT: Duration = Duration::from_secs(5);
+/// After a first failed ping, check again this soon before giving up on the pool.
+const RECHECK_INTERVAL: Duration = Duration::from_secs(2);
+/// Failed pings in a row after which the pool is considered dead.
+const FAILURES_BEFORE_DISCONNECT: u32 = 2;
+const MIN_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+
+/// Health of the open sessions by connection id, shared with the connections tab.
+pub type SharedHealth = Rc<RefCell<HashMap<u32, HealthStatus>>>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HealthStatus {
+    Connected,
+    /// Pings are slow or the last one failed.
+    Degraded,
+    /// The pool stopped answering and is being reopened with backoff.
+    Disconnected,
+}
+
+impl HealthStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            HealthStatus::Connected => "connected",
+            HealthStatus::Degraded => "degraded",
+            HealthStatus::Disconnected => "disconnected",
+        }
+    }
+}
+
+/// What the app should do for a session whose check is due.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HealthAction {
+    Ping,
+    Reconnect,
+}
+
+/// Schedules the health checks and reconnect attempts of one session.
+#[derive(Debug, Clone)]
+pub struct HealthMonitor {
+    pub status: HealthStatus,
+    pub latency: Option<Duration>,
+    pub last_error: Option<String>,
+    /// Failed pings or reconnects in a row.
+    failures: u32,
+    next_run: Instant,
+    /// A ping or reconnect is running in the background.
+    in_flight: bool,
+}
+
+impl HealthMonitor {
+    pub fn new(now: Instant) -> Self {
+        Self {
+            status: HealthStatus::Connected,
+            latency: None,
+            last_error: None,
+            failures: 0,
+            next_run: now + CHECK_INTERVAL,
+            in_flight: false,
+        }
+    }
+
+    /// The action to start now, if any. Marks it as running until its result
+    /// is recorded.
+    pub fn poll(&mut self, now: Instant) -> Option<HealthAction> {
+        if self.in_flight || now < self.next_run {
+            return None;
+        }
+        self.in_flight = true;
+        Some(match self.status {
+            HealthStatus::Disconnected => HealthAction::Reconnect,
+            _ => HealthAction::Ping,
+        })
+    }
+
+    /// Starts a reconnect right away unless one is already running. Returns
+    /// whether the caller should reconnect.
+    pub fn start_manual_reconnect(&mut self) -> bool {
+        if self.in_flight {
+            return false;
+        }
+        self.in_flight = true;
+        true
+    }
+
+    pub fn record_ping(&mut self, result: Result<Duration, String>, now: Instant) {
+        self.in_flight = false;
+        match result {
+            Ok(latency) => {
+                self.failures = 0;
+                self.latency = Some(latency);
+                self.last_error = None;
+                self.status = if latency > SLOW_PING {
+                    HealthStatus::Degraded
+                } else {
+                    HealthStatus::Connected
+                };
+                self.next_run = now + CHECK_INTERVAL;
+            }
+            Err(error) => {
+                self.failures += 1;
+                self.latency = None;
+                self.last_error = Some(error);
+                if self.failures >= FAILURES_BEFORE_DISCONNECT {
+                    self.status = HealthStatus::Disconnected;
+                    // the first reconnect goes out right away
+                    self.failures = 0;
+                    self.next_run = now;
+                } else {
+                    self.status = HealthStatus::Degraded;
+                    self.next_run = now + RECHECK_INTERVAL;
+                }
+            }
+        }
+    }
+
+    pub fn record_reconnect_failure(&mut self, error: String, now: Instant) {
+        self.in_flight = false;
+        self.failures += 1;
+        self.status = HealthStatus::Disconnected;
+        self.last_error = Some(error);
+        self.next_run = now + backoff(self.failures);
+    }
+
+    /// Time until the next reconnect attempt, while disconnected.
+    pub fn retry_in(&self, now: Instant) -> Option<Duration> {
+        if self.status != HealthStatus::Disconnected || self.in_flight {
+            return None;
+        }
+        Some(self.next_run.saturating_duration_since(now))
+    }
+
+    pub fn is_reconnecting(&self) -> bool {
+        self.status == HealthStatus::Disconnected && self.in_flight
+    }
+}
+
+/// Exponential backoff: 1s, 2s, 4s, ... capped at a minute.
+fn backoff(failures: u32) -> Duration {
+    let factor = 1u32 << failures.saturating_sub(1).min(6);
+    (MIN_BACKOFF * factor).min(MAX_BACKOFF)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_backoff() {
+        assert_eq!(backoff(1), Duration::from_secs(1));
+        assert_eq!(backoff(2), Duration::from_secs(2));
+        assert_eq!(backoff(4), Duration::from_secs(8));
+        assert_eq!(backoff(7), MAX_BACKOFF);
+        assert_eq!(backoff(100), MAX_BACKOFF);
+    }
+
+    #[test]
+    fn test_failed_pings_lead_to_reconnects() {
+        let start = Instant::now();
+        let mut monitor = HealthMonitor::new(start);
+        assert_eq!(monitor.poll(start), None);
+
+        let now = start + CHECK_INTERVAL;
+        assert_eq!(monitor.poll(now), Some(HealthAction::Ping));
+        // nothing new while the ping runs
+        assert_eq!(monitor.poll(now), None);
+        monitor.record_ping(Ok(Duration::from_secs(2)), now);
+        assert_eq!(monitor.status, HealthStatus::Degraded);
+
+        let now = now + CHECK_INTERVAL;
+        monitor.poll(now);
+        monitor.record_ping(Err("connection reset".to_string()), now);
+        assert_eq!(monitor.status, HealthStatus::Degraded);
+
+        let now = now + RECHECK_INTERVAL;
+        assert_eq!(monitor.poll(now), Some(HealthAction::Ping));
+        monitor.record_ping(Err("connection reset".to_string()), now);
+        assert_eq!(monitor.status, HealthStatus::Disconnected);
+        assert_eq!(monitor.poll(now), Some(HealthAction::Reconnect));
+        assert!(monitor.is_reconnecting());
+
+        monitor.record_reconnect_failure("refused".to_string(), now);
+        assert_eq!(monitor.retry_in(now), Some(Duration::from_secs(1)));
+        monitor.poll(now + Duration::from_secs(1));
+        monitor.record_reconnect_failure("refused".to_string(), now);
+        assert_eq!(monitor.retry_in(now), Some(Duration::from_secs(2)));
+        assert_eq!(monitor.poll(now), None);
+
+        assert!(monitor.start_manual_reconnect());
+        assert!(!monitor.start_manual_reconnect());
+    }
+}
@@ -0,0 +1,464 @@
+use std::{collections::BTreeMap, env, fs, path::PathBuf};
+
+use anyhow::{Context, Result};
+use log::{debug, warn};
+
+use super::{
+    connection::{Connection, PasswordSource},
+    ssh_tunnel::expand_home,
+    tls::TlsConfig,
+};
+use crate::tabs::db_types_tab::DBTypes;
+
+const MY_CNF_CLIENT_GROUP: &str = "client";
+
+/// A connection found in one of the client config files, not saved yet.
+#[derive(Debug, Clone)]
+pub struct ImportCandidate {
+    pub connection: Connection,
+    /// Where it was found, e.g. `~/.pgpass:3`.
+    pub source: String,
+    /// The file has a password for it, looked up again on every connect.
+    pub has_password: bool,
+}
+
+/// Reads `~/.pgpass`, `~/.pg_service.conf` and `~/.my.cnf` (or the files named
+/// by `PGPASSFILE` and `PGSERVICEFILE`). Missing files are skipped.
+pub fn discover() -> Vec<ImportCandidate> {
+    let mut candidates = vec![];
+
+    if let Some(content) = read_optional(&pgpass_path()) {
+        candidates.extend(pgpass_candidates(&content));
+    }
+    if let Some(content) = read_optional(&pg_service_path()) {
+        let pgpass = read_optional(&pgpass_path()).unwrap_or_default();
+        candidates.extend(pg_service_candidates(&content, &pgpass));
+    }
+    if let Some(content) = read_optional(&my_cnf_path()) {
+        candidates.extend(my_cnf_candidates(&content));
+    }
+    candidates
+}
+
+/// Looks up the password of a connection imported from a client config file.
+/// `Ok(None)` when the file has none for it (any more).
+pub fn resolve_password(connection: &Connection) -> Result<Option<String>> {
+    match &connection.password_source {
+        PasswordSource::PgPass => {
+            let content = read_required(&pgpass_path())?;
+            Ok(pgpass_lookup(&content, connection))
+        }
+        PasswordSource::PgService { service } => {
+            let content = read_required(&pg_service_path())?;
+            let from_service = parse_ini(&content)
+                .remove(service)
+                .and_then(|mut values| values.remove("password"));
+            match from_service {
+                Some(password) => Ok(Some(password)),
+                // libpq falls back to the password file as well
+                None => Ok(read_optional(&pgpass_path())
+                    .and_then(|content| pgpass_lookup(&content, connection))),
+            }
+        }
+        PasswordSource::MyCnf { group } => {
+            let content = read_required(&my_cnf_path())?;
+            let groups = parse_ini(&content);
+            Ok(my_cnf_group(&groups, group).remove("password"))
+        }
+        PasswordSource::Stored | PasswordSource::Ask | PasswordSource::Command { .. } => Ok(None),
+    }
+}
+
+fn pgpass_path() -> PathBuf {
+    match env::var("PGPASSFILE") {
+        Ok(path) if !path.is_empty() => PathBuf::from(path),
+        _ => expand_home("~/.pgpass"),
+    }
+}
+
+fn pg_service_path() -> PathBuf {
+    match env::var("PGSERVICEFILE") {
+        Ok(path) if !path.is_empty() => PathBuf::from(path),
+        _ => expand_home("~/.pg_service.conf"),
+    }
+}
+
+fn my_cnf_path() -> PathBuf {
+    expand_home("~/.my.cnf")
+}
+
+fn read_optional(path: &PathBuf) -> Option<String> {
+    if !path.exists() {
+        debug!("{:?} does not exist, nothing to import", path);
+        return None;
+    }
+    fs::read_to_string(path)
+        .map_err(|e| warn!("failed to read {:?}: {}", path, e))
+        .ok()
+}
+
+fn read_required(path: &PathBuf) -> Result<String> {
+    fs::read_to_string(path).with_context(|| format!("failed to read {}", path.display()))
+}
+
+/// One line of a `.pgpass` file: `hostname:port:database:username:password`,
+/// where every field but the password may be `*`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct PgPassEntry {
+    line: usize,
+    host: String,
+    port: String,
+    database: String,
+    user: String,
+    password: String,
+}
+
+fn parse_pgpass(content: &str) -> Vec<PgPassEntry> {
+    content
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty() && !line.trim_start().starts_with('#'))
+        .filter_map(|(index, line)| {
+            let fields = split_pgpass_line(line);
+            let [host, port, database, user, password] = <[String; 5]>::try_from(fields).ok()?;
+            Some(PgPassEntry {
+                line: index + 1,
+                host,
+                port,
+                database,
+                user,
+                password,
+            })
+        })
+        .collect()
+}
+
+/// Splits on `:`, honouring `\:` and `\\` escapes.
+fn split_pgpass_line(line: &str) -> Vec<String> {
+    let mut fields = vec![String::new()];
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => {
+                if let Some(escaped) = chars.next() {
+                    fields.last_mut().unwrap().push(escaped);
+                }
+            }
+            ':' => fields.push(String::new()),
+            c => fields.last_mut().unwrap().push(c),
+        }
+    }
+    fields
+}
+
+fn pgpass_field_matches(pattern: &str, value: &str) -> bool {
+    pattern == "*" || pattern == value
+}
+
+/// The password of the first entry that matches `connection`, like libpq.
+fn pgpass_lookup(content: &str, connection: &Connection) -> Option<String> {
+    // socket connections match `localhost`, like in libpq
+    let host = if connection.host.is_empty() || !connection.socket.is_empty() {
+        "localhost"
+    } else {
+        connection.host.as_str()
+    };
+    let database = if connection.database.is_empty() {
+        connection.user.as_str()
+    } else {
+        connection.database.as_str()
+    };
+    parse_pgpass(content)
+        .into_iter()
+        .find(|entry| {
+            pgpass_field_matches(&entry.host, host)
+                && pgpass_field_matches(&entry.port, &connection.port().to_string())
+                && pgpass_field_matches(&entry.database, database)
+                && pgpass_field_matches(&entry.user, &connection.user)
+        })
+        .map(|entry| entry.password)
+}
+
+fn pgpass_candidates(content: &str) -> Vec<ImportCandidate> {
+    parse_pgpass(content)
+        .into_iter()
+        .map(|entry| {
+            let wildcard = |value: &str| if value == "*" { "" } else { value }.to_string();
+            let mut connection = Connection {
+                db_type: DBTypes::POSTGRES,
+                host: match entry.host.as_str() {
+                    "*" => "localhost".to_string(),
+                    host => host.to_string(),
+                },
+                port: entry.port.parse().unwrap_or(0),
+                database: wildcard(&entry.database),
+                user: wildcard(&entry.user),
+                password_source: PasswordSource::PgPass,
+                ..Default::default()
+            };
+            connection.name = connection.summary();
+            ImportCandidate {
+                connection,
+                source: format!("~/.pgpass:{}", entry.line),
+                has_password: !entry.password.is_empty(),
+            }
+        })
+        .collect()
+}
+
+fn pg_service_candidates(content: &str, pgpass: &str) -> Vec<ImportCandidate> {
+    parse_ini(content)
+        .into_iter()
+        .map(|(service, mut values)| {
+            let password = values.remove("password");
+            let mut connection = Connection {
+                name: service.clone(),
+                db_type: DBTypes::POSTGRES,
+                host: values
+                    .remove("host")
+                    .unwrap_or_else(|| "localhost".to_string()),
+                port: values
+                    .remove("port")
+                    .and_then(|port| port.parse().ok())
+                    .unwrap_or(0),
+                database: values.remove("dbname").unwrap_or_default(),
+                user: values.remove("user").unwrap_or_default(),
+                ..Default::default()
+            };
+            // a host starting with a slash is the socket directory
+            if connection.host.starts_with('/') {
+                connection.socket = std::mem::take(&mut connection.host);
+            }
+            connection.tls = TlsConfig::take_from_params(&mut values).unwrap_or_else(|e| {
+                warn!("ignoring TLS settings of service {}: {:#}", service, e);
+                TlsConfig::default()
+            });
+
+            let in_pgpass = pgpass_lookup(pgpass, &connection).is_some();
+            connection.password_source = if password.is_some() {
+                PasswordSource::PgService {
+                    service: service.clone(),
+                }
+            } else {
+                PasswordSource::PgPass
+            };
+            ImportCandidate {
+                connection,
+                source: format!("~/.pg_service.conf [{}]", service),
+                has_password: password.is_some() || in_pgpass,
+            }
+        })
+        .collect()
+}
+
+/// The options of `group` on top of the `[client]` group, as the mysql client
+/// reads them with `--defaults-group-suffix`.
+fn my_cnf_group(
+    groups: &BTreeMap<String, BTreeMap<String, String>>,
+    group: &str,
+) -> BTreeMap<String, String> {
+    let mut values = groups.get(MY_CNF_CLIENT_GROUP).cloned().unwrap_or_default();
+    if group != MY_CNF_CLIENT_GROUP {
+        values.extend(groups.get(group).cloned().unwrap_or_default());
+    }
+    values
+}
+
+/// One connection for `[client]` and for every `[client<suffix>]` group.
+fn my_cnf_candidates(content: &str) -> Vec<ImportCandidate> {
+    let groups = parse_ini(content);
+    groups
+        .keys()
+        .filter(|group| group.starts_with(MY_CNF_CLIENT_GROUP))
+        .map(|group| {
+            let mut values = my_cnf_group(&groups, group);
+            let password = values.remove("password");
+            let mut connection = Connection {
+                db_type: DBTypes::MYSQL,
+                host: values
+                    .remove("host")
+                    .unwrap_or_else(|| "localhost".to_string()),
+                port: values
+                    .remove("port")
+                    .and_then(|port| port.parse().ok())
+                    .unwrap_or(0),
+                database: values.remove("database").unwrap_or_default(),
+                user: values.remove("user").unwrap_or_default(),
+                socket: values.remove("socket").unwrap_or_default(),
+                password_source: PasswordSource::MyCnf {
+                    group: group.clone(),
+                },
+                ..Default::default()
+            };
+            connection.tls = TlsConfig::take_from_params(&mut values).unwrap_or_default();
+            connection.name = if group == MY_CNF_CLIENT_GROUP {
+                connection.summary()
+            } else {
+                group.clone()
+            };
+            ImportCandidate {
+                connection,
+                source: format!("~/.my.cnf [{}]", group),
+                has_password: password.is_some(),
+            }
+        })
+        .collect()
+}
+
+/// Minimal INI reader for `pg_service.conf` and `my.cnf`: `[group]` headers,
+/// `key = value` lines, `#` and `;` comments. Quotes around values are
+/// removed and `_` in keys is read as `-`, as the mysql client does.
+fn parse_ini(content: &str) -> BTreeMap<String, BTreeMap<String, String>> {
+    let mut groups: BTreeMap<String, BTreeMap<String, String>> = BTreeMap::new();
+    let mut current: Option<String> = None;
+    for line in content.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') || line.starts_with(';') {
+            continue;
+        }
+        if let Some(group) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            let group = group.trim().to_string();
+            groups.entry(group.clone()).or_default();
+            current = Some(group);
+            continue;
+        }
+        // `!include` and friends, and options outside of a group
+        let Some(group) = &current else {
+            continue;
+        };
+        if line.starts_with('!') {
+            continue;
+        }
+        let (key, value) = line.split_once('=').unwrap_or((line, ""));
+        let value = value.trim();
+        let value = value
+            .strip_prefix('"')
+            .and_then(|v| v.strip_suffix('"'))
+            .or_else(|| value.strip_prefix('\'').and_then(|v| v.strip_suffix('\'')))
+            .unwrap_or(value);
+        groups
+            .entry(group.clone())
+            .or_default()
+            .insert(key.trim().replace('_', "-"), value.to_string());
+    }
+    groups
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::tls::SslMode;
+    use pretty_assertions::assert_eq;
+
+    const PGPASS: &str = r"# comment
+db.example.com:5432:orders:app:s3cr\:et
+*:*:*:postgres:local
+broken line
+";
+
+    #[test]
+    fn test_pgpass() {
+        let candidates = pgpass_candidates(PGPASS);
+        assert_eq!(candidates.len(), 2);
+        assert_eq!(
+            candidates[0].connection.summary(),
+            "app@db.example.com:5432/orders"
+        );
+        assert_eq!(candidates[0].source, "~/.pgpass:2");
+        assert_eq!(
+            candidates[1].connection.summary(),
+            "postgres@localhost:5432"
+        );
+        assert_eq!(
+            candidates[1].connection.password_source,
+            PasswordSource::PgPass
+        );
+
+        let lookup = |host: &str, database: &str, user: &str| {
+            let connection = Connection {
+                db_type: DBTypes::POSTGRES,
+                host: host.to_string(),
+                database: database.to_string(),
+                user: user.to_string(),
+                ..Default::default()
+            };
+            pgpass_lookup(PGPASS, &connection)
+        };
+        assert_eq!(
+            lookup("db.example.com", "orders", "app"),
+            Some("s3cr:et".to_string())
+        );
+        assert_eq!(
+            lookup("anywhere", "any", "postgres"),
+            Some("local".to_string())
+        );
+        assert_eq!(lookup("db.example.com", "billing", "app"), None);
+    }
+
+    #[test]
+    fn test_pg_service() {
+        let services = "
+[reporting]
+host=reports.internal
+port=5433
+dbname=dw
+user=analyst
+sslmode=verify-full
+
+[local]
+host=/var/run/postgresql
+dbname=dev
+password=devpass
+";
+        let pgpass = "reports.internal:5433:dw:analyst:secret\n";
+
+        let candidates = pg_service_candidates(services, pgpass);
+        assert_eq!(candidates.len(), 2);
+        let local = &candidates[0];
+        assert_eq!(local.connection.name, "local");
+        assert_eq!(local.connection.socket, "/var/run/postgresql");
+        assert_eq!(local.connection.summary(), "unix:/var/run/postgresql/dev");
+        assert_eq!(
+            local.connection.password_source,
+            PasswordSource::PgService {
+                service: "local".to_string()
+            }
+        );
+        let reporting = &candidates[1].connection;
+        assert_eq!(reporting.summary(), "analyst@reports.internal:5433/dw");
+        assert_eq!(reporting.tls.mode, SslMode::VerifyFull);
+        assert_eq!(reporting.password_source, PasswordSource::PgPass);
+        assert!(candidates[1].has_password);
+    }
+
+    #[test]
+    fn test_my_cnf() {
+        let my_cnf = r#"
+[client]
+user = root
+password = "p#ss"
+
+[client_prod]
+host = db.prod.internal
+ssl_ca = /etc/ssl/ca.pem
+
+[mysqldump]
+quick
+"#;
+
+        let candidates = my_cnf_candidates(my_cnf);
+        assert_eq!(candidates.len(), 2);
+        assert_eq!(candidates[0].connection.summary(), "root@localhost:3306");
+        let prod = &candidates[1];
+        assert_eq!(prod.connection.name, "client_prod");
+        assert_eq!(prod.connection.summary(), "root@db.prod.internal:3306");
+        assert_eq!(prod.connection.tls.ca_file, "/etc/ssl/ca.pem");
+        assert!(prod.has_password);
+
+        let groups = parse_ini(my_cnf);
+        assert_eq!(
+            my_cnf_group(&groups, "client_prod").get("password"),
+            Some(&"p#ss".to_string())
+        );
+    }
+}
@@ -0,0 +1,16 @@
+pub mod catalog;
+pub mod config_file;
+pub mod connection;
+pub mod connection_url;
+pub mod credentials;
+pub mod health;
+pub mod import;
+pub mod password_command;
+pub mod pool;
+pub mod redact;
+pub mod session;
+pub mod sql;
+pub mod ssh_tunnel;
+pub mod store;
+pub mod table_keys;
+pub mod tls;
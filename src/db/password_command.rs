@@ -0,0 +1,95 @@
+use std::{process::Stdio, time::Duration};
+
+use anyhow::{anyhow, bail, Context, Result};
+use log::debug;
+use tokio::process::Command;
+
+use super::{
+    connection::{Connection, PasswordSource},
+    redact::redact_command,
+};
+
+/// Long enough to answer a pinentry or a hardware key prompt.
+const COMMAND_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Runs the password command of `connection`, if it has one, and puts its
+/// output into `connection.password`.
+pub async fn resolve(connection: &mut Connection) -> Result<()> {
+    if let PasswordSource::Command { command } = &connection.password_source {
+        debug!("running the password command of {}", connection.name);
+        connection.password = run(command).await?;
+    }
+    Ok(())
+}
+
+/// Runs `command` through the shell and returns the first line it prints,
+/// like `pass show` puts the password on the first line.
+///
+/// The output is never logged and never part of an error, errors name only the
+/// program of the command since its arguments may hold a token.
+pub async fn run(command: &str) -> Result<String> {
+    let program = redact_command(command);
+    let (shell, flag) = if cfg!(windows) {
+        ("cmd", "/C")
+    } else {
+        ("sh", "-c")
+    };
+    let child = Command::new(shell)
+        .args([flag, command])
+        // the terminal belongs to the TUI
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .with_context(|| format!("failed to start password command '{}'", program))?;
+
+    let output = tokio::time::timeout(COMMAND_TIMEOUT, child.wait_with_output())
+        .await
+        .map_err(|_| {
+            anyhow!(
+                "password command '{}' did not finish within {}s",
+                program,
+                COMMAND_TIMEOUT.as_secs()
+            )
+        })?
+        .with_context(|| format!("password command '{}' failed", program))?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        bail!(
+            "password command '{}' exited with {}: {}",
+            program,
+            output.status,
+            stderr.trim()
+        );
+    }
+
+    let stdout = String::from_utf8(output.stdout)
+        .map_err(|_| anyhow!("password command '{}' printed invalid UTF-8", program))?;
+    let password = stdout.lines().next().unwrap_or_default();
+    if password.is_empty() {
+        bail!("password command '{}' printed no password", program);
+    }
+    Ok(password.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[tokio::test]
+    async fn test_run() {
+        assert_eq!(run("echo 's3cret'").await.unwrap(), "s3cret");
+        // only the first line, `pass` keeps metadata below it
+        assert_eq!(run("printf 'p@ss\\nuser: app\\n'").await.unwrap(), "p@ss");
+
+        let error = run("echo oops >&2; exit 3").await.unwrap_err().to_string();
+        assert!(error.contains("exited with"), "{}", error);
+        assert!(error.ends_with("oops"), "{}", error);
+
+        let error = run("true").await.unwrap_err().to_string();
+        assert!(error.ends_with("printed no password"), "{}", error);
+    }
+}
@@ -0,0 +1,463 @@
+use std::{
+    str::FromStr,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use anyhow::{anyhow, Result};
+use log::debug;
+use serde::{Deserialize, Serialize};
+use sqlx::{
+    mysql::{MySqlConnectOptions, MySqlPoolOptions},
+    pool::PoolOptions,
+    postgres::{PgConnectOptions, PgPoolOptions},
+    sqlite::{SqliteConnectOptions, SqlitePoolOptions},
+    Column, Database, Executor, MySqlPool, PgPool, Row, SqlitePool,
+};
+
+use super::{connection::Connection, password_command, redact::is_secret_param, ssh_tunnel};
+use crate::tabs::db_types_tab::DBTypes;
+
+/// Pool size, timeouts and the SQL run on every new physical connection.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PoolConfig {
+    pub max_connections: u32,
+    /// How long to wait for a connection, including establishing a new one.
+    pub connect_timeout_secs: u64,
+    /// Idle connections are closed after this long, 0 keeps them open.
+    pub idle_timeout_secs: u64,
+    /// Statements running longer are cancelled by the server, 0 for no limit.
+    /// Not supported by SQLite.
+    pub statement_timeout_secs: u64,
+    /// Run after connecting, e.g. `SET search_path TO app` or `PRAGMA foreign_keys = ON`.
+    pub init_sql: String,
+}
+
+impl Default for PoolConfig {
+    fn default() -> Self {
+        Self {
+            max_connections: 5,
+            connect_timeout_secs: 30,
+            idle_timeout_secs: 600,
+            statement_timeout_secs: 0,
+            init_sql: String::new(),
+        }
+    }
+}
+
+impl PoolConfig {
+    fn pool_options<DB: Database>(&self) -> PoolOptions<DB> {
+        PoolOptions::new()
+            .max_connections(self.max_connections.max(1))
+            .acquire_timeout(Duration::from_secs(self.connect_timeout_secs.max(1)))
+            .idle_timeout(
+                (self.idle_timeout_secs > 0).then(|| Duration::from_secs(self.idle_timeout_secs)),
+            )
+    }
+}
+
+/// The statements that set up every new physical connection of a pool: the
+/// read-only guard, the statement timeout and the user's init SQL, in that order.
+fn session_statements(connection: &Connection, read_only: bool) -> Vec<String> {
+    let config = &connection.pool;
+    let mut statements = vec![];
+    if read_only {
+        match connection.db_type {
+            DBTypes::POSTGRES => {
+                statements.push("SET SESSION CHARACTERISTICS AS TRANSACTION READ ONLY".to_string())
+            }
+            DBTypes::MYSQL | DBTypes::MARIA => {
+                statements.push("SET SESSION TRANSACTION READ ONLY".to_string())
+            }
+            // SQLite opens the file read-only instead
+            DBTypes::SQLITE => {}
+        }
+    }
+    if config.statement_timeout_secs > 0 {
+        let millis = config.statement_timeout_secs * 1000;
+        match connection.db_type {
+            DBTypes::POSTGRES => statements.push(format!("SET statement_timeout = {}", millis)),
+            DBTypes::MYSQL => {
+                statements.push(format!("SET SESSION max_execution_time = {}", millis))
+            }
+            DBTypes::MARIA => statements.push(format!(
+                "SET SESSION max_statement_time = {}",
+                config.statement_timeout_secs
+            )),
+            DBTypes::SQLITE => {}
+        }
+    }
+    if !config.init_sql.trim().is_empty() {
+        statements.push(config.init_sql.trim().to_string());
+    }
+    statements
+}
+
+/// Registers `statements` to run on every new physical connection.
+macro_rules! run_on_connect {
+    ($pool_options:expr, $statements:expr) => {{
+        let statements: Arc<Vec<String>> = Arc::new($statements);
+        if statements.is_empty() {
+            $pool_options
+        } else {
+            $pool_options.after_connect(move |conn, _| {
+                let statements = statements.clone();
+                Box::pin(async move {
+                    for statement in statements.iter() {
+                        conn.execute(sqlx::raw_sql(statement)).await?;
+                    }
+                    Ok(())
+                })
+            })
+        }
+    }};
+}
+
+/// A live sqlx pool for one of the supported drivers.
+#[derive(Debug, Clone)]
+pub enum DbPool {
+    Postgres(PgPool),
+    MySql(MySqlPool),
+    Sqlite(SqlitePool),
+}
+
+impl DbPool {
+    /// Opens a pool for `connection` and makes sure at least one physical
+    /// connection can be established. With `read_only` every connection of
+    /// the pool refuses writes on the server side.
+    pub async fn connect(connection: &Connection, read_only: bool) -> Result<Self> {
+        let url = driver_url(connection);
+        let statements = session_statements(connection, read_only);
+        let pool = match connection.db_type {
+            DBTypes::POSTGRES => {
+                let options = connection.tls.apply_pg(PgConnectOptions::from_str(&url)?);
+                let pool_options: PgPoolOptions = connection.pool.pool_options();
+                let pool_options = run_on_connect!(pool_options, statements);
+                DbPool::Postgres(pool_options.connect_with(options).await?)
+            }
+            DBTypes::MYSQL | DBTypes::MARIA => {
+                let mut options = connection
+                    .tls
+                    .apply_mysql(MySqlConnectOptions::from_str(&url)?);
+                if !connection.socket.is_empty() && connection.user.is_empty() {
+                    // peer authentication (auth_socket / unix_socket) logs in as
+                    // the OS user, sqlx would default to root. Postgres already
+                    // falls back to the OS user.
+                    if let Some(user) = os_user() {
+                        options = options.username(&user);
+                    }
+                }
+                let pool_options: MySqlPoolOptions = connection.pool.pool_options();
+                let pool_options = run_on_connect!(pool_options, statements);
+                DbPool::MySql(pool_options.connect_with(options).await?)
+            }
+            DBTypes::SQLITE => {
+                // same as `?mode=ro` in the URL
+                let options = SqliteConnectOptions::from_str(&url)?
+                    .create_if_missing(connection.create_if_missing && !read_only)
+                    .read_only(read_only);
+                let pool_options: SqlitePoolOptions = connection.pool.pool_options();
+                let pool_options = run_on_connect!(pool_options, statements);
+                DbPool::Sqlite(pool_options.connect_with(options).await?)
+            }
+        };
+        Ok(pool)
+    }
+
+    /// Runs a trivial query and returns how long the round trip took.
+    pub async fn ping(&self) -> Result<Duration> {
+        let started = Instant::now();
+        match self {
+            DbPool::Postgres(pool) => sqlx::query("SELECT 1").execute(pool).await.map(|_| ())?,
+            DbPool::MySql(pool) => sqlx::query("SELECT 1").execute(pool).await.map(|_| ())?,
+            DbPool::Sqlite(pool) => sqlx::query("SELECT 1").execute(pool).await.map(|_| ())?,
+        };
+        Ok(started.elapsed())
+    }
+
+    pub async fn server_version(&self) -> Result<String> {
+        let version = match self {
+            DbPool::Postgres(pool) => {
+                sqlx::query_scalar("SHOW server_version")
+                    .fetch_one(pool)
+                    .await?
+            }
+            DbPool::MySql(pool) => {
+                sqlx::query_scalar("SELECT VERSION()")
+                    .fetch_one(pool)
+                    .await?
+            }
+            DbPool::Sqlite(pool) => {
+                sqlx::query_scalar("SELECT sqlite_version()")
+                    .fetch_one(pool)
+                    .await?
+            }
+        };
+        Ok(version)
+    }
+
+    /// Runs one or more statements and returns the number of affected rows.
+    pub async fn execute(&self, sql: &str) -> Result<u64> {
+        let rows = match self {
+            DbPool::Postgres(pool) => sqlx::raw_sql(sql).execute(pool).await?.rows_affected(),
+            DbPool::MySql(pool) => sqlx::raw_sql(sql).execute(pool).await?.rows_affected(),
+            DbPool::Sqlite(pool) => sqlx::raw_sql(sql).execute(pool).await?.rows_affected(),
+        };
+        Ok(rows)
+    }
+
+    /// Runs a query and returns its rows as text. The statement goes over the
+    /// text protocol, so every column type (dates, numerics, arrays, ...) comes
+    /// back the way the server prints it.
+    pub async fn fetch(&self, sql: &str) -> Result<QueryResult> {
+        let result = match self {
+            DbPool::Postgres(pool) => {
+                QueryResult::from_rows(sqlx::raw_sql(sql).fetch_all(pool).await?)
+            }
+            DbPool::MySql(pool) => {
+                QueryResult::from_rows(sqlx::raw_sql(sql).fetch_all(pool).await?)
+            }
+            DbPool::Sqlite(pool) => {
+                QueryResult::from_rows(sqlx::raw_sql(sql).fetch_all(pool).await?)
+            }
+        };
+        Ok(result)
+    }
+
+    pub async fn close(&self) {
+        match self {
+            DbPool::Postgres(pool) => pool.close().await,
+            DbPool::MySql(pool) => pool.close().await,
+            DbPool::Sqlite(pool) => pool.close().await,
+        }
+    }
+}
+
+/// Name of the user running the app, for peer authentication over a unix socket.
+fn os_user() -> Option<String> {
+    std::env::var("USER")
+        .or_else(|_| std::env::var("LOGNAME"))
+        .ok()
+        .filter(|user| !user.is_empty())
+}
+
+/// Rows returned by [`DbPool::fetch`], `None` for NULL.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct QueryResult {
+    pub columns: Vec<String>,
+    pub rows: Vec<Vec<Option<String>>>,
+}
+
+impl QueryResult {
+    fn from_rows<R: Row>(rows: Vec<R>) -> Self
+    where
+        for<'r> Option<String>: sqlx::Decode<'r, R::Database>,
+        for<'r> Option<Vec<u8>>: sqlx::Decode<'r, R::Database>,
+        usize: sqlx::ColumnIndex<R>,
+    {
+        let columns = rows
+            .first()
+            .map(|row| {
+                row.columns()
+                    .iter()
+                    .map(|column| column.name().to_string())
+                    .collect()
+            })
+            .unwrap_or_default();
+        let rows = rows
+            .iter()
+            .map(|row| (0..row.len()).map(|i| text_value(row, i)).collect())
+            .collect();
+        Self { columns, rows }
+    }
+}
+
+/// Column `i` of `row` as text; binary data that is not UTF-8 is shown as hex.
+fn text_value<R: Row>(row: &R, i: usize) -> Option<String>
+where
+    for<'r> Option<String>: sqlx::Decode<'r, R::Database>,
+    for<'r> Option<Vec<u8>>: sqlx::Decode<'r, R::Database>,
+    usize: sqlx::ColumnIndex<R>,
+{
+    // unchecked: the text protocol sends every type as a string
+    match row.try_get_unchecked::<Option<String>, _>(i) {
+        Ok(value) => value,
+        Err(_) => row
+            .try_get_unchecked::<Option<Vec<u8>>, _>(i)
+            .ok()
+            .flatten()
+            .map(|bytes| {
+                let hex: String = bytes.iter().map(|b| format!("{:02x}", b)).collect();
+                format!("\\x{}", hex)
+            }),
+    }
+}
+
+/// The URL sqlx connects with. sqlx logs the value of every Postgres
+/// parameter it does not know, so secret ones it does not support, like
+/// `sslpassword`, are left out.
+fn driver_url(connection: &Connection) -> String {
+    let mut connection = connection.clone();
+    if connection.db_type == DBTypes::POSTGRES {
+        connection.params.retain(|key, _| {
+            let supported = key == "password" || !is_secret_param(key);
+            if !supported {
+                debug!("ignoring unsupported connect parameter {}", key);
+            }
+            supported
+        });
+    }
+    connection.to_url()
+}
+
+/// Result of a successful "test connection".
+#[derive(Debug, Clone)]
+pub struct ConnectionTestReport {
+    pub server_version: String,
+    pub latency: Duration,
+}
+
+/// Runs the password command, opens a throw-away pool (and SSH tunnel) for `connection`, reports the
+/// server version and round-trip latency, and closes it again. Gives up after `timeout`.
+pub async fn test_connection(
+    connection: &Connection,
+    timeout: Duration,
+) -> Result<ConnectionTestReport> {
+    let attempt = async {
+        let mut connection = connection.clone();
+        password_command::resolve(&mut connection).await?;
+        let (target, tunnel) = ssh_tunnel::forward(&connection).await?;
+        let pool = DbPool::connect(&target, connection.read_only).await?;
+        let latency = pool.ping().await?;
+        let server_version = pool.server_version().await?;
+        pool.close().await;
+        if let Some(tunnel) = tunnel {
+            tunnel.close().await;
+        }
+        Ok(ConnectionTestReport {
+            server_version,
+            latency,
+        })
+    };
+
+    tokio::time::timeout(timeout, attempt)
+        .await
+        .map_err(|_| anyhow!("timed out after {}s", timeout.as_secs()))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_connect_sqlite_create_if_missing() {
+        let dir = TempDir::new().unwrap();
+        let mut connection = Connection {
+            db_type: DBTypes::SQLITE,
+            database: dir.path().join("app.db").to_string_lossy().into_owned(),
+            ..Default::default()
+        };
+
+        assert!(DbPool::connect(&connection, false).await.is_err());
+
+        connection.create_if_missing = true;
+        let pool = DbPool::connect(&connection, false).await.unwrap();
+        assert!(matches!(pool, DbPool::Sqlite(_)));
+        pool.close().await;
+    }
+
+    #[tokio::test]
+    async fn test_read_only_rejects_writes() {
+        let dir = TempDir::new().unwrap();
+        let connection = Connection {
+            db_type: DBTypes::SQLITE,
+            database: dir.path().join("app.db").to_string_lossy().into_owned(),
+            create_if_missing: true,
+            ..Default::default()
+        };
+
+        let pool = DbPool::connect(&connection, false).await.unwrap();
+        pool.execute("CREATE TABLE t (id INTEGER)").await.unwrap();
+        pool.close().await;
+
+        let pool = DbPool::connect(&connection, true).await.unwrap();
+        assert!(pool.execute("SELECT * FROM t").await.is_ok());
+        assert!(pool.execute("INSERT INTO t VALUES (1)").await.is_err());
+        pool.close().await;
+    }
+
+    #[test]
+    fn test_session_statements() {
+        let mut connection = Connection {
+            db_type: DBTypes::POSTGRES,
+            pool: PoolConfig {
+                statement_timeout_secs: 30,
+                init_sql: " SET search_path TO app ".to_string(),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        assert_eq!(
+            session_statements(&connection, true),
+            vec![
+                "SET SESSION CHARACTERISTICS AS TRANSACTION READ ONLY",
+                "SET statement_timeout = 30000",
+                "SET search_path TO app",
+            ]
+        );
+
+        connection.db_type = DBTypes::MARIA;
+        assert_eq!(
+            session_statements(&connection, false)[0],
+            "SET SESSION max_statement_time = 30"
+        );
+
+        connection.db_type = DBTypes::SQLITE;
+        assert_eq!(
+            session_statements(&connection, true),
+            vec!["SET search_path TO app"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_init_sql_runs_on_every_connection() {
+        let dir = TempDir::new().unwrap();
+        let connection = Connection {
+            db_type: DBTypes::SQLITE,
+            database: dir.path().join("app.db").to_string_lossy().into_owned(),
+            create_if_missing: true,
+            pool: PoolConfig {
+                init_sql: "PRAGMA foreign_keys = ON".to_string(),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let pool = DbPool::connect(&connection, false).await.unwrap();
+        pool.execute("CREATE TABLE parent (id INTEGER PRIMARY KEY)")
+            .await
+            .unwrap();
+        pool.execute("CREATE TABLE child (parent_id INTEGER REFERENCES parent (id))")
+            .await
+            .unwrap();
+        // rejected only because foreign keys were switched on
+        assert!(pool.execute("INSERT INTO child VALUES (1)").await.is_err());
+        pool.close().await;
+    }
+
+    #[tokio::test]
+    async fn test_test_connection_reports_version() {
+        let connection = Connection {
+            db_type: DBTypes::SQLITE,
+            database: ":memory:".to_string(),
+            ..Default::default()
+        };
+
+        let report = test_connection(&connection, Duration::from_secs(5))
+            .await
+            .unwrap();
+        assert!(report.server_version.starts_with('3'));
+    }
+}
@@ -0,0 +1,120 @@
+use std::{collections::HashMap, sync::Arc, time::Instant};
+
+use anyhow::Result;
+use tokio::sync::Mutex;
+
+use super::{
+    connection::Connection,
+    health::HealthMonitor,
+    password_command,
+    pool::DbPool,
+    ssh_tunnel::{self, SshTunnel},
+};
+
+/// An open connection: the saved entry it was created from plus its live pool
+/// and the SSH tunnel the pool goes through, if any.
+#[derive(Debug)]
+pub struct Session {
+    /// Set by the app, unique among the open sessions. Unlike the connection id
+    /// it is also set for connections that are not saved.
+    pub id: u32,
+    pub connection: Connection,
+    pub pool: DbPool,
+    /// Pools for browsing the other databases of the server.
+    pub databases: DatabasePools,
+    /// Either the connection is marked read-only or `--read-only` was given.
+    pub read_only: bool,
+    /// Tab that was shown while this session was active, restored when switching back to it.
+    pub tab_index: usize,
+    pub health: HealthMonitor,
+    tunnel: Option<SshTunnel>,
+}
+
+impl Session {
+    /// Runs the password command and starts the SSH tunnel when configured,
+    /// then opens the pool. `force_read_only` opens it read-only even if the
+    /// connection is not.
+    pub async fn open(mut connection: Connection, force_read_only: bool) -> Result<Self> {
+        password_command::resolve(&mut connection).await?;
+        let read_only = connection.read_only || force_read_only;
+        let (target, tunnel) = ssh_tunnel::forward(&connection).await?;
+        let pool = DbPool::connect(&target, read_only).await?;
+        Ok(Self {
+            id: 0,
+            connection,
+            databases: DatabasePools::new(pool.clone(), target, read_only),
+            pool,
+            read_only,
+            tab_index: 0,
+            health: HealthMonitor::new(Instant::now()),
+            tunnel,
+        })
+    }
+
+    /// Closes the pool first so connections are not cut off by the tunnel going away.
+    pub async fn close(self) {
+        self.databases.close().await;
+        self.pool.close().await;
+        if let Some(tunnel) = self.tunnel {
+            tunnel.close().await;
+        }
+    }
+}
+
+/// Hands out a pool for any database of the session's server. Postgres
+/// connections are bound to one database, so every other database gets a
+/// small pool of its own, opened on first use. MySQL and SQLite reach all
+/// their databases through the session's pool.
+#[derive(Debug, Clone)]
+pub struct DatabasePools {
+    main: DbPool,
+    /// What the session's pool connected to, behind the SSH tunnel.
+    target: Connection,
+    read_only: bool,
+    others: Arc<Mutex<HashMap<String, DbPool>>>,
+}
+
+impl DatabasePools {
+    /// Pool size for databases other than the session's one, they only serve
+    /// the catalog tabs.
+    const MAX_CONNECTIONS: u32 = 2;
+
+    fn new(main: DbPool, target: Connection, read_only: bool) -> Self {
+        Self {
+            main,
+            target,
+            read_only,
+            others: Arc::default(),
+        }
+    }
+
+    pub async fn get(&self, database: &str) -> Result<DbPool> {
+        let DbPool::Postgres(main) = &self.main else {
+            return Ok(self.main.clone());
+        };
+        let current: String = sqlx::query_scalar("SELECT current_database()::text")
+            .fetch_one(main)
+            .await?;
+        if current == database {
+            return Ok(self.main.clone());
+        }
+
+        let mut others = self.others.lock().await;
+        if let Some(pool) = others.get(database) {
+            return Ok(pool.clone());
+        }
+        let mut target = self.target.clone();
+        target.database = database.to_string();
+        target.pool.max_connections = Self::MAX_CONNECTIONS;
+        let pool = DbPool::connect(&target, self.read_only).await?;
+        others.insert(database.to_string(), pool.clone());
+        Ok(pool)
+    }
+
+    /// Closes the pools of the other databases, the session's own is left alone.
+    async fn close(&self) {
+        for (_, pool) in self.others.lock().await.drain() {
+            pool.close().await;
+        }
+    }
+}
@@ -0,0 +1,267 @@
+/// Keywords that modify data when they show up inside a `WITH` or `EXPLAIN ANALYZE`.
+const WRITE_KEYWORDS: [&str; 4] = ["INSERT", "UPDATE", "DELETE", "MERGE"];
+
+/// Keywords and type names that may be followed by `(` without it being a
+/// function call.
+const KEYWORDS_BEFORE_PAREN: &[&str] = &[
+    "ALL", "AND", "ANY", "AS", "BY", "CASE", "CHAR", "DECIMAL", "ELSE", "EXISTS", "FILTER", "FROM",
+    "IN", "JOIN", "LATERAL", "NOT", "NUMERIC", "ON", "OR", "OVER", "ROW", "SELECT", "SOME", "THEN",
+    "UNION", "USING", "VALUES", "VARCHAR", "WHEN", "WHERE", "WITH", "WITHIN",
+];
+
+/// Functions known not to change anything. Any other call, e.g. `setval`,
+/// `nextval` or `pg_terminate_backend`, makes a query count as a write.
+const READ_ONLY_FUNCTIONS: &[&str] = &[
+    "ABS",
+    "ARRAY_AGG",
+    "AVG",
+    "CAST",
+    "CEIL",
+    "CEILING",
+    "CHAR_LENGTH",
+    "COALESCE",
+    "CONCAT",
+    "COUNT",
+    "CURRENT_SCHEMA",
+    "CURRENT_USER",
+    "DATE",
+    "DATE_TRUNC",
+    "DENSE_RANK",
+    "EXTRACT",
+    "FLOOR",
+    "GREATEST",
+    "GROUP_CONCAT",
+    "IF",
+    "IFNULL",
+    "JSON_AGG",
+    "LAG",
+    "LEAD",
+    "LEAST",
+    "LENGTH",
+    "LOWER",
+    "MAX",
+    "MIN",
+    "NOW",
+    "NULLIF",
+    "RANK",
+    "REPLACE",
+    "ROUND",
+    "ROW_NUMBER",
+    "STRING_AGG",
+    "SUBSTR",
+    "SUBSTRING",
+    "SUM",
+    "TO_CHAR",
+    "UPPER",
+    "VERSION",
+];
+
+/// Token standing in for a string literal.
+const LITERAL: &str = "'";
+/// Token standing in for a quoted identifier.
+const QUOTED_NAME: &str = "\"";
+
+/// Whether running `sql` may change data, schema or session state. Unknown
+/// statements count as writes so that guards fail safe.
+///
+/// Backslashes escape quotes on MySQL but not in standard SQL, so the text is
+/// read both ways and counts as a write if either reading finds one.
+pub fn is_write_statement(sql: &str) -> bool {
+    [false, true].into_iter().any(|backslash_escapes| {
+        split_statements(sql, backslash_escapes)
+            .iter()
+            .any(|words| statement_writes(words))
+    })
+}
+
+fn statement_writes(words: &[String]) -> bool {
+    let Some(first) = words.first() else {
+        return false;
+    };
+    let contains = |keyword: &str| words.iter().any(|word| word == keyword);
+
+    match first.as_str() {
+        "SELECT" | "WITH" | "VALUES" | "TABLE" => query_writes(words),
+        "SHOW" | "DESCRIBE" | "DESC" | "USE" => false,
+        "BEGIN" | "START" | "COMMIT" | "ROLLBACK" | "END" => false,
+        "EXPLAIN" => contains("ANALYZE") && query_writes(words),
+        // `PRAGMA name` reads a setting, `PRAGMA name = value` changes it
+        "PRAGMA" => contains("="),
+        _ => true,
+    }
+}
+
+/// Whether a query writes: data changing statements inside it, `SELECT ...
+/// INTO new_table`, row locks or a call to a function that may have side
+/// effects.
+fn query_writes(words: &[String]) -> bool {
+    let contains = |keyword: &str| words.iter().any(|word| word == keyword);
+    if contains("INTO") || WRITE_KEYWORDS.iter().any(|keyword| contains(keyword)) {
+        return true;
+    }
+
+    words
+        .windows(2)
+        .any(|pair| match (pair[0].as_str(), pair[1].as_str()) {
+            // FOR UPDATE / SHARE / NO KEY UPDATE / KEY SHARE, LOCK IN SHARE MODE
+            ("FOR", "UPDATE" | "SHARE" | "NO" | "KEY") | ("LOCK", "IN") => true,
+            (name, "(") => is_name(name) && !is_known_read_only(name),
+            _ => false,
+        })
+}
+
+fn is_name(word: &str) -> bool {
+    word == QUOTED_NAME || word.starts_with(|c: char| c.is_alphabetic() || c == '_')
+}
+
+fn is_known_read_only(name: &str) -> bool {
+    KEYWORDS_BEFORE_PAREN.contains(&name) || READ_ONLY_FUNCTIONS.contains(&name)
+}
+
+/// Splits `sql` into statements made of upper-cased words and punctuation,
+/// leaving out comments. String literals become [`LITERAL`] and quoted
+/// identifiers [`QUOTED_NAME`], including Postgres `$tag$` dollar quotes.
+fn split_statements(sql: &str, backslash_escapes: bool) -> Vec<Vec<String>> {
+    let chars: Vec<char> = sql.chars().collect();
+    let mut statements = vec![];
+    let mut words: Vec<String> = vec![];
+    let mut word = String::new();
+    let mut i = 0;
+
+    let finish_word = |word: &mut String, words: &mut Vec<String>| {
+        if !word.is_empty() {
+            words.push(word.to_ascii_uppercase());
+            word.clear();
+        }
+    };
+
+    while i < chars.len() {
+        let c = chars[i];
+        let next = chars.get(i + 1).copied();
+        i += 1;
+        match c {
+            '-' if next == Some('-') => {
+                finish_word(&mut word, &mut words);
+                while i < chars.len() && chars[i] != '\n' {
+                    i += 1;
+                }
+            }
+            '/' if next == Some('*') => {
+                finish_word(&mut word, &mut words);
+                i += 1;
+                while i < chars.len() && !(chars[i - 1] == '*' && chars[i] == '/') {
+                    i += 1;
+                }
+                i += 1;
+            }
+            '\'' | '"' | '`' => {
+                finish_word(&mut word, &mut words);
+                while i < chars.len() && chars[i] != c {
+                    if backslash_escapes && chars[i] == '\\' {
+                        i += 1;
+                    }
+                    i += 1;
+                }
+                i += 1;
+                words.push(if c == '\'' { LITERAL } else { QUOTED_NAME }.to_string());
+            }
+            '$' if word.is_empty() => match dollar_quote_end(&chars, i) {
+                Some(end) => {
+                    words.push(LITERAL.to_string());
+                    i = end;
+                }
+                // a `$1` parameter
+                None => words.push(c.to_string()),
+            },
+            ';' => {
+                finish_word(&mut word, &mut words);
+                if !words.is_empty() {
+                    statements.push(std::mem::take(&mut words));
+                }
+            }
+            c if c.is_alphanumeric() || c == '_' || (c == '$' && !word.is_empty()) => word.push(c),
+            c if c.is_whitespace() => finish_word(&mut word, &mut words),
+            c => {
+                finish_word(&mut word, &mut words);
+                words.push(c.to_string());
+            }
+        }
+    }
+    finish_word(&mut word, &mut words);
+    if !words.is_empty() {
+        statements.push(words);
+    }
+    statements
+}
+
+/// For a `$` at `start - 1` opening a dollar quote (`$$` or `$tag$`), the
+/// index right after its closing delimiter, or the end of the text.
+fn dollar_quote_end(chars: &[char], start: usize) -> Option<usize> {
+    let tag_len = chars[start..]
+        .iter()
+        .position(|c| !(c.is_alphanumeric() || *c == '_'))?;
+    if chars[start + tag_len] != '$' || chars.get(start).is_some_and(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    let delimiter = &chars[start - 1..=start + tag_len];
+    let body = start + tag_len + 1;
+    Some(
+        (body..chars.len())
+            .find(|&i| chars[i..].starts_with(delimiter))
+            .map_or(chars.len(), |i| i + delimiter.len()),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_write_statement() {
+        for sql in [
+            "select * from users",
+            "  -- delete everything\n SELECT 'DELETE FROM users'",
+            "SHOW TABLES;",
+            "with recent as (select * from orders) select count(*) from recent",
+            "EXPLAIN SELECT 1",
+            "PRAGMA table_info(users)",
+            "SELECT lower(name), count(*) FROM users WHERE id IN (1, 2) GROUP BY 1",
+            "SELECT $$ ; drop table users $$",
+            "SELECT $body$ it's ; delete $body$ AS doc",
+            "SELECT * FROM users WHERE id = $1",
+            "SELECT price::numeric(10, 2) FROM items",
+            "",
+        ] {
+            assert!(!is_write_statement(sql), "{}", sql);
+        }
+
+        for sql in [
+            "UPDATE users SET name = 'x'",
+            "select 1; drop table users",
+            "/* harmless */ insert into t values (1)",
+            "WITH gone AS (DELETE FROM t RETURNING *) SELECT * FROM gone",
+            "EXPLAIN ANALYZE DELETE FROM t",
+            "SELECT * INTO backup FROM users",
+            "PRAGMA journal_mode = WAL",
+            "SET search_path TO public",
+            "VACUUM",
+            "SELECT setval('users_id_seq', 1)",
+            "SELECT nextval('users_id_seq')",
+            "SELECT pg_terminate_backend(pid) FROM pg_stat_activity",
+            "select pg_catalog.set_config('role', 'admin', false)",
+            "SELECT \"my_function\"()",
+            "SELECT * FROM accounts WHERE id = 1 FOR UPDATE",
+            "SELECT * FROM accounts FOR SHARE",
+            "SELECT * FROM accounts FOR NO KEY UPDATE",
+            "SELECT * FROM accounts LOCK IN SHARE MODE",
+            "EXPLAIN ANALYZE SELECT nextval('s')",
+            // Postgres keeps the backslash, the string ends before `;`
+            r"SELECT 'C:\'; DROP TABLE users; --'",
+            // MySQL escapes the quote, the string ends after `;`
+            r"SELECT 'it\'s'; DROP TABLE users",
+            "SELECT $$ unterminated ; $$; DELETE FROM t",
+        ] {
+            assert!(is_write_statement(sql), "{}", sql);
+        }
+    }
+}
@@ -0,0 +1,333 @@
+use std::{
+    net::{Ipv4Addr, TcpListener},
+    path::PathBuf,
+    process::Stdio,
+    time::Duration,
+};
+
+use anyhow::{anyhow, Context, Result};
+use log::{debug, error};
+use serde::{Deserialize, Serialize};
+use tokio::{
+    io::AsyncReadExt,
+    net::TcpStream,
+    process::{Child, Command},
+    time::{sleep, Instant},
+};
+
+use super::{connection::Connection, tls::SslMode};
+
+const DEFAULT_SSH_PORT: u16 = 22;
+
+/// How long to wait for ssh to authenticate and start listening.
+const READY_TIMEOUT: Duration = Duration::from_secs(15);
+const READY_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Bastion host a connection is reached through.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SshConfig {
+    pub host: String,
+    pub port: u16,
+    /// Empty to let ssh pick the user (current user or `~/.ssh/config`).
+    pub user: String,
+    /// Private key file. Empty to use the ssh agent and `~/.ssh/config`.
+    pub key_file: String,
+    /// Only connect to bastions already in `known_hosts`. Off by default: the
+    /// key of a new bastion is trusted and remembered on first connect.
+    pub strict_host_key: bool,
+}
+
+impl Default for SshConfig {
+    fn default() -> Self {
+        Self {
+            host: String::new(),
+            port: DEFAULT_SSH_PORT,
+            user: String::new(),
+            key_file: String::new(),
+            strict_host_key: false,
+        }
+    }
+}
+
+/// A local port forward run by the system `ssh` client. The process is
+/// killed when the tunnel is closed or dropped.
+#[derive(Debug)]
+pub struct SshTunnel {
+    child: Child,
+    local_port: u16,
+}
+
+impl SshTunnel {
+    /// Forwards a free port on 127.0.0.1 to `remote_host:remote_port` as seen
+    /// from the bastion, and waits until the forward accepts connections.
+    pub async fn open(config: &SshConfig, remote_host: &str, remote_port: u16) -> Result<Self> {
+        let local_port = free_local_port()?;
+        let args = ssh_args(config, local_port, remote_host, remote_port);
+        debug!("starting ssh {}", args.join(" "));
+
+        let child = Command::new("ssh")
+            .args(&args)
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()
+            .context("failed to start ssh")?;
+
+        let mut tunnel = Self { child, local_port };
+        tunnel.wait_until_ready().await?;
+        debug!(
+            "ssh tunnel 127.0.0.1:{} -> {}:{} via {} is up",
+            local_port, remote_host, remote_port, config.host
+        );
+        Ok(tunnel)
+    }
+
+    pub fn local_port(&self) -> u16 {
+        self.local_port
+    }
+
+    pub async fn close(mut self) {
+        if let Err(e) = self.child.kill().await {
+            error!("failed to stop ssh tunnel: {:?}", e);
+        }
+    }
+
+    async fn wait_until_ready(&mut self) -> Result<()> {
+        let deadline = Instant::now() + READY_TIMEOUT;
+        loop {
+            if let Some(status) = self.child.try_wait()? {
+                let mut stderr = String::new();
+                if let Some(mut pipe) = self.child.stderr.take() {
+                    let _ = pipe.read_to_string(&mut stderr).await;
+                }
+                return Err(anyhow!("ssh tunnel failed ({}): {}", status, stderr.trim()));
+            }
+            if TcpStream::connect((Ipv4Addr::LOCALHOST, self.local_port))
+                .await
+                .is_ok()
+            {
+                return Ok(());
+            }
+            if Instant::now() >= deadline {
+                return Err(anyhow!(
+                    "ssh tunnel did not come up within {}s",
+                    READY_TIMEOUT.as_secs()
+                ));
+            }
+            sleep(READY_POLL_INTERVAL).await;
+        }
+    }
+}
+
+/// Starts the tunnel configured for `connection`, if any. Returns the
+/// connection the pool should use, pointed at the local end of the tunnel.
+pub async fn forward(connection: &Connection) -> Result<(Connection, Option<SshTunnel>)> {
+    let Some(config) = &connection.ssh else {
+        return Ok((connection.clone(), None));
+    };
+    if !connection.socket.is_empty() {
+        return Err(anyhow!(
+            "an SSH tunnel forwards a host and port, it cannot be combined with a unix socket"
+        ));
+    }
+    // the pool only sees 127.0.0.1, which never matches the server certificate
+    if connection.tls.mode == SslMode::VerifyFull {
+        return Err(anyhow!(
+            "TLS mode verify-full checks the host name, which cannot work through an SSH tunnel; use verify-ca"
+        ));
+    }
+
+    let tunnel = SshTunnel::open(config, &connection.host, connection.port()).await?;
+    let target = Connection {
+        host: Ipv4Addr::LOCALHOST.to_string(),
+        port: tunnel.local_port(),
+        ..connection.clone()
+    };
+    Ok((target, Some(tunnel)))
+}
+
+fn ssh_args(
+    config: &SshConfig,
+    local_port: u16,
+    remote_host: &str,
+    remote_port: u16,
+) -> Vec<String> {
+    let remote_host = if remote_host.contains(':') {
+        format!("[{}]", remote_host)
+    } else {
+        remote_host.to_string()
+    };
+
+    let mut args: Vec<String> = vec![
+        "-N".into(),
+        // never prompt, the terminal belongs to the TUI
+        "-o".into(),
+        "BatchMode=yes".into(),
+        "-o".into(),
+        "ExitOnForwardFailure=yes".into(),
+        "-o".into(),
+        if config.strict_host_key {
+            "StrictHostKeyChecking=yes".into()
+        } else {
+            "StrictHostKeyChecking=accept-new".into()
+        },
+        "-o".into(),
+        "ConnectTimeout=10".into(),
+        "-L".into(),
+        format!("127.0.0.1:{}:{}:{}", local_port, remote_host, remote_port),
+        "-p".into(),
+        config.port.to_string(),
+    ];
+    if !config.user.is_empty() {
+        args.push("-l".into());
+        args.push(config.user.clone());
+    }
+    if !config.key_file.is_empty() {
+        args.push("-i".into());
+        args.push(expand_home(&config.key_file).to_string_lossy().into_owned());
+        args.push("-o".into());
+        args.push("IdentitiesOnly=yes".into());
+    }
+    // a host starting with `-` must not be taken for an option
+    args.push("--".into());
+    args.push(config.host.clone());
+    args
+}
+
+/// Expands a leading `~/` the way a shell would.
+pub fn expand_home(path: &str) -> PathBuf {
+    match (path.strip_prefix("~/"), dirs::home_dir()) {
+        (Some(rest), Some(home)) => home.join(rest),
+        _ => PathBuf::from(path),
+    }
+}
+
+fn free_local_port() -> Result<u16> {
+    let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0))
+        .context("failed to find a free local port for the ssh tunnel")?;
+    Ok(listener.local_addr()?.port())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_ssh_args() {
+        let config = SshConfig {
+            host: "bastion.example.com".to_string(),
+            port: 2222,
+            user: "deploy".to_string(),
+            key_file: "/keys/id_ed25519".to_string(),
+            ..Default::default()
+        };
+
+        let args = ssh_args(&config, 40000, "db.internal", 5432);
+        assert_eq!(args[0], "-N");
+        let forward = args.iter().position(|arg| arg == "-L").unwrap();
+        assert_eq!(args[forward + 1], "127.0.0.1:40000:db.internal:5432");
+        assert!(args.windows(2).any(|w| w == ["-p", "2222"]));
+        assert!(args.windows(2).any(|w| w == ["-l", "deploy"]));
+        assert!(args.windows(2).any(|w| w == ["-i", "/keys/id_ed25519"]));
+        assert!(args.contains(&"StrictHostKeyChecking=accept-new".to_string()));
+        assert_eq!(args[args.len() - 2..], ["--", "bastion.example.com"]);
+
+        // agent auth and ipv6 targets
+        let config = SshConfig {
+            host: "bastion".to_string(),
+            ..Default::default()
+        };
+        let args = ssh_args(&config, 40000, "::1", 3306);
+        assert!(args.contains(&"127.0.0.1:40000:[::1]:3306".to_string()));
+        assert!(!args.contains(&"-i".to_string()));
+        assert!(!args.contains(&"-l".to_string()));
+
+        let config = SshConfig {
+            host: "-oProxyCommand=touch /tmp/pwned".to_string(),
+            strict_host_key: true,
+            ..Default::default()
+        };
+        let args = ssh_args(&config, 40000, "db", 5432);
+        assert!(args.contains(&"StrictHostKeyChecking=yes".to_string()));
+        assert_eq!(
+            args[args.len() - 2..],
+            ["--", "-oProxyCommand=touch /tmp/pwned"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_unreachable_bastion_fails() {
+        let config = SshConfig {
+            host: "127.0.0.1".to_string(),
+            // nothing listens on the discard port
+            port: 9,
+            ..Default::default()
+        };
+
+        match SshTunnel::open(&config, "localhost", 5432).await {
+            // without an ssh client there is nothing to test
+            Err(e) if e.to_string() == "failed to start ssh" => {}
+            Err(e) => assert!(e.to_string().starts_with("ssh tunnel failed"), "{:#}", e),
+            Ok(_) => panic!("tunnel through a closed port came up"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_verify_full_is_rejected() {
+        let mut connection = Connection {
+            host: "db.internal".to_string(),
+            ssh: Some(SshConfig {
+                host: "bastion".to_string(),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        connection.tls.mode = SslMode::VerifyFull;
+
+        let Err(e) = forward(&connection).await else {
+            panic!("verify-full was tunneled");
+        };
+        assert!(e.to_string().contains("verify-ca"), "{:#}", e);
+    }
+
+    /// Needs an sshd on 127.0.0.1 (port `DB_TUI_TEST_SSH_PORT`, default 22)
+    /// that the current user can log in to without a prompt, e.g. with a key
+    /// from the agent. Run with `cargo test -- --ignored`.
+    #[tokio::test]
+    #[ignore]
+    async fn test_tunnel_through_local_sshd() {
+        use tokio::{io::AsyncWriteExt, net::TcpListener};
+
+        let target = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).await.unwrap();
+        let target_port = target.local_addr().unwrap().port();
+        // the readiness probe connects through the tunnel as well
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = target.accept().await {
+                let _ = stream.write_all(b"hello through ssh").await;
+            }
+        });
+
+        let config = SshConfig {
+            host: "127.0.0.1".to_string(),
+            port: std::env::var("DB_TUI_TEST_SSH_PORT")
+                .ok()
+                .and_then(|port| port.parse().ok())
+                .unwrap_or(DEFAULT_SSH_PORT),
+            ..Default::default()
+        };
+        let tunnel = SshTunnel::open(&config, "127.0.0.1", target_port)
+            .await
+            .unwrap();
+
+        let mut stream = TcpStream::connect((Ipv4Addr::LOCALHOST, tunnel.local_port()))
+            .await
+            .unwrap();
+        let mut received = String::new();
+        stream.read_to_string(&mut received).await.unwrap();
+        assert_eq!(received, "hello through ssh");
+        tunnel.close().await;
+    }
+}
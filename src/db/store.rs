@@ -0,0 +1,371 @@
+use std::{cell::RefCell, fs, path::PathBuf, rc::Rc};
+
+use anyhow::{anyhow, Context, Result};
+use log::{debug, error};
+use ron::ser::{to_string_pretty, PrettyConfig};
+use serde::{Deserialize, Serialize};
+
+use super::{
+    config_file::{backup_broken_file, NewerVersion},
+    connection::Connection,
+};
+use crate::args::get_app_config_path;
+
+pub type SharedConnections = Rc<RefCell<ConnectionStore>>;
+
+const CONNECTIONS_FILE: &str = "connections.ron";
+
+/// Version of the on-disk format written by this build.
+/// Bump it when a change can not be expressed by adding defaulted fields.
+const CURRENT_VERSION: u32 = 1;
+
+#[derive(Debug, Serialize, Deserialize)]
+struct ConnectionsFile {
+    version: u32,
+    #[serde(default)]
+    connections: Vec<Connection>,
+}
+
+/// Saved connections, backed by a RON file in the app config directory.
+#[derive(Debug, Default)]
+pub struct ConnectionStore {
+    path: PathBuf,
+    connections: Vec<Connection>,
+    /// Why the file must not be written, e.g. it comes from a newer version.
+    save_blocked: Option<String>,
+}
+
+impl ConnectionStore {
+    /// Loads the store from the default config location.
+    /// A missing or unparsable file results in an empty store, the broken file
+    /// is kept as a backup. A file from a newer version, one that can not be
+    /// read or a broken one that can not be backed up is left alone and the
+    /// empty store refuses to save over it.
+    pub fn load_default() -> Self {
+        let path = match get_app_config_path() {
+            Ok(dir) => dir.join(CONNECTIONS_FILE),
+            Err(e) => {
+                error!("failed to locate config dir: {:?}", e);
+                return Self::default();
+            }
+        };
+
+        Self::load(path.clone()).unwrap_or_else(|e| Self::load_failed(path, e))
+    }
+
+    fn load_failed(path: PathBuf, e: anyhow::Error) -> Self {
+        error!("failed to load saved connections: {:?}", e);
+        let save_blocked = if e.is::<ron::error::SpannedError>() {
+            // without a backup the broken file is the only copy left
+            match backup_broken_file(&path) {
+                Some(_) => None,
+                None => Some(format!("{:#}, and it could not be backed up", e)),
+            }
+        } else {
+            Some(format!("{:#}", e))
+        };
+        Self {
+            path,
+            connections: vec![],
+            save_blocked,
+        }
+    }
+
+    pub fn load(path: PathBuf) -> Result<Self> {
+        if !path.exists() {
+            debug!("no saved connections at {:?}", path);
+            return Ok(Self {
+                path,
+                ..Default::default()
+            });
+        }
+
+        let content = fs::read_to_string(&path)
+            .with_context(|| format!("failed to read {}", path.display()))?;
+        let file: ConnectionsFile = ron::from_str(&content)
+            .with_context(|| format!("failed to parse {}", path.display()))?;
+
+        if file.version > CURRENT_VERSION {
+            return Err(NewerVersion {
+                path,
+                version: file.version,
+                supported: CURRENT_VERSION,
+            }
+            .into());
+        }
+
+        Ok(Self {
+            path,
+            connections: file.connections,
+            save_blocked: None,
+        })
+    }
+
+    fn save(&self, connections: &[Connection]) -> Result<()> {
+        if let Some(reason) = &self.save_blocked {
+            return Err(anyhow!("saved connections are not written: {}", reason));
+        }
+        if self.path.as_os_str().is_empty() {
+            return Err(anyhow!("connections store has no file path"));
+        }
+
+        let file = ConnectionsFile {
+            version: CURRENT_VERSION,
+            connections: connections.to_vec(),
+        };
+        let content = to_string_pretty(&file, PrettyConfig::default())?;
+
+        // write to a temp file first so a crash never leaves a truncated file behind
+        let tmp_path = self.path.with_extension("ron.tmp");
+        fs::write(&tmp_path, content)
+            .with_context(|| format!("failed to write {}", tmp_path.display()))?;
+        fs::rename(&tmp_path, &self.path)
+            .with_context(|| format!("failed to write {}", self.path.display()))?;
+
+        debug!("saved {} connections", connections.len());
+        Ok(())
+    }
+
+    /// Saves `connections` and only then takes them over, so a failed save
+    /// leaves the store as it was.
+    fn replace(&mut self, connections: Vec<Connection>) -> Result<()> {
+        self.save(&connections)?;
+        self.connections = connections;
+        Ok(())
+    }
+
+    pub fn connections(&self) -> &[Connection] {
+        &self.connections
+    }
+
+    /// Adds a connection, assigning it a fresh id, and saves the store.
+    pub fn add(&mut self, mut connection: Connection) -> Result<u32> {
+        connection.id = self.next_id();
+        let id = connection.id;
+        let mut connections = self.connections.clone();
+        connections.push(connection);
+        self.replace(connections)?;
+        Ok(id)
+    }
+
+    pub fn get(&self, id: u32) -> Option<&Connection> {
+        self.connections.iter().find(|conn| conn.id == id)
+    }
+
+    /// Replaces the entry with the same id and saves the store.
+    pub fn update(&mut self, connection: Connection) -> Result<()> {
+        let mut connections = self.connections.clone();
+        let existing = connections
+            .iter_mut()
+            .find(|conn| conn.id == connection.id)
+            .ok_or_else(|| anyhow!("connection {} does not exist", connection.id))?;
+        *existing = connection;
+        self.replace(connections)
+    }
+
+    pub fn remove(&mut self, id: u32) -> Result<()> {
+        let mut connections = self.connections.clone();
+        connections.retain(|conn| conn.id != id);
+        if connections.len() == self.connections.len() {
+            return Err(anyhow!("connection {} does not exist", id));
+        }
+        self.replace(connections)
+    }
+
+    pub fn rename(&mut self, id: u32, name: &str) -> Result<()> {
+        let mut connection = self
+            .get(id)
+            .cloned()
+            .ok_or_else(|| anyhow!("connection {} does not exist", id))?;
+        connection.name = name.to_string();
+        self.update(connection)
+    }
+
+    /// Adds a copy of the entry right after the original and returns the new id.
+    pub fn duplicate(&mut self, id: u32) -> Result<u32> {
+        let index = self
+            .connections
+            .iter()
+            .position(|conn| conn.id == id)
+            .ok_or_else(|| anyhow!("connection {} does not exist", id))?;
+
+        let mut copy = self.connections[index].clone();
+        copy.id = self.next_id();
+        copy.name = format!("{} (copy)", copy.name);
+        let new_id = copy.id;
+        let mut connections = self.connections.clone();
+        connections.insert(index + 1, copy);
+        self.replace(connections)?;
+        Ok(new_id)
+    }
+
+    fn next_id(&self) -> u32 {
+        self.connections
+            .iter()
+            .map(|conn| conn.id)
+            .max()
+            .map_or(1, |id| id + 1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tabs::db_types_tab::DBTypes;
+    use pretty_assertions::assert_eq;
+    use tempfile::TempDir;
+
+    fn connection(name: &str) -> Connection {
+        Connection {
+            name: name.to_string(),
+            db_type: DBTypes::MYSQL,
+            host: "localhost".to_string(),
+            port: 3306,
+            user: "root".to_string(),
+            database: "app".to_string(),
+            password: "secret".to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join(CONNECTIONS_FILE);
+
+        let mut store = ConnectionStore::load(path.clone()).unwrap();
+        assert_eq!(store.add(connection("first")).unwrap(), 1);
+        assert_eq!(store.add(connection("second")).unwrap(), 2);
+
+        let loaded = ConnectionStore::load(path).unwrap();
+        let names: Vec<&str> = loaded
+            .connections()
+            .iter()
+            .map(|c| c.name.as_str())
+            .collect();
+        assert_eq!(names, vec!["first", "second"]);
+        assert_eq!(loaded.connections()[1].db_type, DBTypes::MYSQL);
+        assert_eq!(loaded.connections()[1].port, 3306);
+        // passwords are never written to the connections file
+        assert_eq!(loaded.connections()[1].password, "");
+    }
+
+    #[test]
+    fn test_edit_actions() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join(CONNECTIONS_FILE);
+
+        let mut store = ConnectionStore::load(path.clone()).unwrap();
+        let first = store.add(connection("first")).unwrap();
+        let second = store.add(connection("second")).unwrap();
+
+        let copy = store.duplicate(first).unwrap();
+        store.rename(second, "renamed").unwrap();
+        let mut edited = store.get(first).unwrap().clone();
+        edited.host = "db.internal".to_string();
+        store.update(edited).unwrap();
+        store.remove(second).unwrap();
+        assert!(store.remove(second).is_err());
+
+        let loaded = ConnectionStore::load(path).unwrap();
+        let names: Vec<&str> = loaded
+            .connections()
+            .iter()
+            .map(|c| c.name.as_str())
+            .collect();
+        assert_eq!(names, vec!["first", "first (copy)"]);
+        assert_eq!(loaded.get(first).unwrap().host, "db.internal");
+        assert_eq!(loaded.get(copy).unwrap().host, "localhost");
+    }
+
+    #[test]
+    fn test_missing_fields_use_defaults() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join(CONNECTIONS_FILE);
+        fs::write(&path, r#"(version: 1, connections: [(name: "old")])"#).unwrap();
+
+        let store = ConnectionStore::load(path).unwrap();
+        assert_eq!(store.connections()[0].name, "old");
+        assert_eq!(store.connections()[0].db_type, DBTypes::POSTGRES);
+    }
+
+    #[test]
+    fn test_rejects_newer_version() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join(CONNECTIONS_FILE);
+        fs::write(&path, "(version: 99, connections: [])").unwrap();
+
+        assert!(ConnectionStore::load(path).is_err());
+    }
+
+    #[test]
+    fn test_newer_version_is_never_overwritten() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join(CONNECTIONS_FILE);
+        let content = "(version: 99, connections: [])";
+        fs::write(&path, content).unwrap();
+
+        let error = ConnectionStore::load(path.clone()).unwrap_err();
+        let mut store = ConnectionStore::load_failed(path.clone(), error);
+        assert!(store.add(connection("new")).is_err());
+        assert!(store.connections().is_empty());
+        assert_eq!(fs::read_to_string(&path).unwrap(), content);
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn test_broken_file_is_backed_up() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join(CONNECTIONS_FILE);
+        fs::write(&path, "(version: 1, connections: [(name: ").unwrap();
+
+        let error = ConnectionStore::load(path.clone()).unwrap_err();
+        let mut store = ConnectionStore::load_failed(path.clone(), error);
+        store.add(connection("new")).unwrap();
+        let backups: Vec<String> = fs::read_dir(dir.path())
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+            .filter(|name| name.ends_with(".bak"))
+            .collect();
+        assert_eq!(backups.len(), 1);
+        assert_eq!(ConnectionStore::load(path).unwrap().connections().len(), 1);
+    }
+
+    #[test]
+    fn test_broken_file_without_backup_is_never_overwritten() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join(CONNECTIONS_FILE);
+        fs::write(&path, "(version: 1, connections: [(name: ").unwrap();
+        let error = ConnectionStore::load(path).unwrap_err();
+
+        // the broken file is gone by the time it is backed up, so the backup fails
+        let path = dir.path().join("moved").join(CONNECTIONS_FILE);
+        fs::create_dir(dir.path().join("moved")).unwrap();
+        let mut store = ConnectionStore::load_failed(path.clone(), error);
+        let error = store.add(connection("new")).unwrap_err();
+        assert!(format!("{:#}", error).contains("could not be backed up"));
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn test_failed_save_keeps_memory_unchanged() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join(CONNECTIONS_FILE);
+        let mut store = ConnectionStore::load(path).unwrap();
+        let id = store.add(connection("first")).unwrap();
+
+        // the temp file can not be created in a directory that is gone
+        store.path = dir.path().join("gone").join(CONNECTIONS_FILE);
+        assert!(store.add(connection("second")).is_err());
+        assert!(store.rename(id, "renamed").is_err());
+        assert!(store.remove(id).is_err());
+        assert!(store.duplicate(id).is_err());
+
+        let names: Vec<&str> = store
+            .connections()
+            .iter()
+            .map(|c| c.name.as_str())
+            .collect();
+        assert_eq!(names, vec!["first"]);
+    }
+}
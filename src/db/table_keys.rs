@@ -0,0 +1,628 @@
+use std::collections::HashMap;
+
+use anyhow::Result;
+use sqlx::{sqlite::SqliteRow, MySqlPool, PgPool, Row, SqlitePool};
+
+use super::{
+    catalog::{qualified_name, quote_identifier, TableInfo},
+    pool::DbPool,
+};
+
+/// An index of a table.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct IndexInfo {
+    pub name: String,
+    /// Column names, or the expression for expression indexes.
+    pub columns: Vec<String>,
+    pub unique: bool,
+    pub primary: bool,
+    /// `btree`, `gin`, `HASH`, `FULLTEXT`, ... `None` for SQLite.
+    pub method: Option<String>,
+    /// `WHERE` clause of a partial index.
+    pub predicate: Option<String>,
+    /// `None` where the server does not tell (MySQL).
+    pub size: Option<i64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConstraintKind {
+    PrimaryKey,
+    Unique,
+    Check,
+}
+
+impl ConstraintKind {
+    /// As written in SQL.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ConstraintKind::PrimaryKey => "PRIMARY KEY",
+            ConstraintKind::Unique => "UNIQUE",
+            ConstraintKind::Check => "CHECK",
+        }
+    }
+
+    /// A primary key or unique constraint on `columns`, in SQL.
+    fn definition(&self, columns: &[String]) -> String {
+        format!("{} ({})", self.as_str(), columns.join(", "))
+    }
+}
+
+/// A primary key, unique or check constraint.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConstraintInfo {
+    /// Empty for unnamed SQLite constraints.
+    pub name: String,
+    pub kind: ConstraintKind,
+    /// In SQL, e.g. `PRIMARY KEY (id)` or `CHECK (total > 0)`.
+    pub definition: String,
+}
+
+/// A foreign key from `from_table` to `to_table`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ForeignKeyInfo {
+    /// Empty for unnamed SQLite foreign keys.
+    pub name: String,
+    pub from_table: String,
+    pub from_columns: Vec<String>,
+    pub to_table: String,
+    /// Empty when a SQLite foreign key refers to the primary key implicitly.
+    pub to_columns: Vec<String>,
+    pub on_update: String,
+    pub on_delete: String,
+}
+
+/// Indexes, constraints and foreign keys of a table.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TableKeys {
+    pub indexes: Vec<IndexInfo>,
+    pub constraints: Vec<ConstraintInfo>,
+    /// Foreign keys of this table.
+    pub outgoing: Vec<ForeignKeyInfo>,
+    /// Foreign keys of other tables (or this one) that refer to this table.
+    pub incoming: Vec<ForeignKeyInfo>,
+}
+
+/// Reads the indexes, constraints and foreign keys of `table` in `database`.
+pub async fn table_keys(pool: &DbPool, database: &str, table: &TableInfo) -> Result<TableKeys> {
+    match pool {
+        DbPool::Postgres(pg_pool) => {
+            postgres_keys(pg_pool, &qualified_name(pool, database, table)).await
+        }
+        DbPool::MySql(pool) => mysql_keys(pool, database, &table.name).await,
+        DbPool::Sqlite(pool) => sqlite_keys(pool, database, &table.name).await,
+    }
+}
+
+async fn postgres_keys(pool: &PgPool, table: &str) -> Result<TableKeys> {
+    let indexes = sqlx::query(
+        "SELECT ic.relname::text AS name,
+                ARRAY(SELECT pg_catalog.pg_get_indexdef(i.indexrelid, k, true)
+                      FROM generate_series(1, i.indnkeyatts) AS k) AS columns,
+                i.indisunique AS is_unique,
+                i.indisprimary AS is_primary,
+                am.amname::text AS method,
+                pg_catalog.pg_get_expr(i.indpred, i.indrelid, true) AS predicate,
+                pg_catalog.pg_relation_size(i.indexrelid) AS size
+         FROM pg_catalog.pg_index i
+         JOIN pg_catalog.pg_class ic ON ic.oid = i.indexrelid
+         JOIN pg_catalog.pg_am am ON am.oid = ic.relam
+         WHERE i.indrelid = $1::regclass
+         ORDER BY i.indisprimary DESC, ic.relname",
+    )
+    .bind(table)
+    .fetch_all(pool)
+    .await?
+    .into_iter()
+    .map(|row| {
+        Ok(IndexInfo {
+            name: row.try_get("name")?,
+            columns: row.try_get("columns")?,
+            unique: row.try_get("is_unique")?,
+            primary: row.try_get("is_primary")?,
+            method: row.try_get("method")?,
+            predicate: row.try_get("predicate")?,
+            size: row.try_get("size")?,
+        })
+    })
+    .collect::<Result<_>>()?;
+
+    let constraints = sqlx::query(
+        "SELECT conname::text AS name,
+                contype::text AS kind,
+                pg_catalog.pg_get_constraintdef(oid, true) AS definition
+         FROM pg_catalog.pg_constraint
+         WHERE conrelid = $1::regclass AND contype IN ('p', 'u', 'c')
+         ORDER BY contype DESC, conname",
+    )
+    .bind(table)
+    .fetch_all(pool)
+    .await?
+    .into_iter()
+    .map(|row| {
+        let kind: String = row.try_get("kind")?;
+        Ok(ConstraintInfo {
+            name: row.try_get("name")?,
+            kind: match kind.as_str() {
+                "p" => ConstraintKind::PrimaryKey,
+                "u" => ConstraintKind::Unique,
+                _ => ConstraintKind::Check,
+            },
+            definition: row.try_get("definition")?,
+        })
+    })
+    .collect::<Result<_>>()?;
+
+    let mut keys = TableKeys {
+        indexes,
+        constraints,
+        ..Default::default()
+    };
+    // regclass::text leaves out schemas on the search path
+    let foreign_keys = sqlx::query(
+        "SELECT c.conname::text AS name,
+                c.conrelid::regclass::text AS from_table,
+                ARRAY(SELECT a.attname::text
+                      FROM unnest(c.conkey) WITH ORDINALITY AS k(attnum, n)
+                      JOIN pg_catalog.pg_attribute a
+                        ON a.attrelid = c.conrelid AND a.attnum = k.attnum
+                      ORDER BY k.n) AS from_columns,
+                c.confrelid::regclass::text AS to_table,
+                ARRAY(SELECT a.attname::text
+                      FROM unnest(c.confkey) WITH ORDINALITY AS k(attnum, n)
+                      JOIN pg_catalog.pg_attribute a
+                        ON a.attrelid = c.confrelid AND a.attnum = k.attnum
+                      ORDER BY k.n) AS to_columns,
+                c.confupdtype::text AS on_update,
+                c.confdeltype::text AS on_delete,
+                c.conrelid = $1::regclass AS outgoing,
+                c.confrelid = $1::regclass AS incoming
+         FROM pg_catalog.pg_constraint c
+         WHERE c.contype = 'f' AND $1::regclass IN (c.conrelid, c.confrelid)
+         ORDER BY c.conname",
+    )
+    .bind(table)
+    .fetch_all(pool)
+    .await?;
+    for row in foreign_keys {
+        let action = |column: &str| -> Result<String> {
+            let code: String = row.try_get(column)?;
+            Ok(match code.as_str() {
+                "r" => "RESTRICT",
+                "c" => "CASCADE",
+                "n" => "SET NULL",
+                "d" => "SET DEFAULT",
+                _ => "NO ACTION",
+            }
+            .to_string())
+        };
+        let foreign_key = ForeignKeyInfo {
+            name: row.try_get("name")?,
+            from_table: row.try_get("from_table")?,
+            from_columns: row.try_get("from_columns")?,
+            to_table: row.try_get("to_table")?,
+            to_columns: row.try_get("to_columns")?,
+            on_update: action("on_update")?,
+            on_delete: action("on_delete")?,
+        };
+        if row.try_get::<bool, _>("incoming")? {
+            keys.incoming.push(foreign_key.clone());
+        }
+        if row.try_get::<bool, _>("outgoing")? {
+            keys.outgoing.push(foreign_key);
+        }
+    }
+    Ok(keys)
+}
+
+async fn mysql_keys(pool: &MySqlPool, database: &str, table: &str) -> Result<TableKeys> {
+    // one row per index column, information_schema has no index sizes
+    let index_columns = sqlx::query(
+        "SELECT CAST(index_name AS CHAR) AS name,
+                CAST(column_name AS CHAR) AS column_name,
+                CAST(sub_part AS SIGNED) AS sub_part,
+                CAST(non_unique AS SIGNED) AS non_unique,
+                CAST(index_type AS CHAR) AS method
+         FROM information_schema.statistics
+         WHERE table_schema = ? AND table_name = ?
+         ORDER BY index_name = 'PRIMARY' DESC, index_name, seq_in_index",
+    )
+    .bind(database)
+    .bind(table)
+    .fetch_all(pool)
+    .await?;
+    let mut indexes: Vec<IndexInfo> = vec![];
+    for row in index_columns {
+        let name: String = row.try_get("name")?;
+        // functional key parts (MySQL 8) have no column name
+        let column: Option<String> = row.try_get("column_name")?;
+        let sub_part: Option<i64> = row.try_get("sub_part")?;
+        let column = match (column, sub_part) {
+            (Some(column), Some(length)) => format!("{}({})", column, length),
+            (Some(column), None) => column,
+            (None, _) => "(expression)".to_string(),
+        };
+        match indexes.last_mut().filter(|index| index.name == name) {
+            Some(index) => index.columns.push(column),
+            None => {
+                let non_unique: i64 = row.try_get("non_unique")?;
+                indexes.push(IndexInfo {
+                    primary: name == "PRIMARY",
+                    name,
+                    columns: vec![column],
+                    unique: non_unique == 0,
+                    method: row.try_get("method")?,
+                    predicate: None,
+                    size: None,
+                });
+            }
+        }
+    }
+
+    // primary keys and unique constraints are their indexes in MySQL
+    let mut constraints: Vec<ConstraintInfo> = indexes
+        .iter()
+        .filter(|index| index.unique)
+        .map(|index| {
+            let kind = if index.primary {
+                ConstraintKind::PrimaryKey
+            } else {
+                ConstraintKind::Unique
+            };
+            ConstraintInfo {
+                name: index.name.clone(),
+                kind,
+                definition: kind.definition(&index.columns),
+            }
+        })
+        .collect();
+    // check_constraints is missing before MySQL 8.0.16 and MariaDB 10.2
+    let checks: Vec<(String, String)> = sqlx::query_as(
+        "SELECT CAST(tc.constraint_name AS CHAR), CAST(cc.check_clause AS CHAR)
+         FROM information_schema.table_constraints tc
+         JOIN information_schema.check_constraints cc
+           ON cc.constraint_schema = tc.constraint_schema
+          AND cc.constraint_name = tc.constraint_name
+         WHERE tc.table_schema = ? AND tc.table_name = ? AND tc.constraint_type = 'CHECK'
+         ORDER BY tc.constraint_name",
+    )
+    .bind(database)
+    .bind(table)
+    .fetch_all(pool)
+    .await
+    .unwrap_or_default();
+    constraints.extend(checks.into_iter().map(|(name, clause)| ConstraintInfo {
+        name,
+        kind: ConstraintKind::Check,
+        definition: format!("{} ({})", ConstraintKind::Check.as_str(), clause),
+    }));
+
+    let key_columns = sqlx::query(
+        "SELECT CAST(k.constraint_name AS CHAR) AS name,
+                CAST(k.table_schema AS CHAR) AS from_schema,
+                CAST(k.table_name AS CHAR) AS from_table,
+                CAST(k.column_name AS CHAR) AS from_column,
+                CAST(k.referenced_table_schema AS CHAR) AS to_schema,
+                CAST(k.referenced_table_name AS CHAR) AS to_table,
+                CAST(k.referenced_column_name AS CHAR) AS to_column,
+                CAST(r.update_rule AS CHAR) AS on_update,
+                CAST(r.delete_rule AS CHAR) AS on_delete
+         FROM information_schema.key_column_usage k
+         JOIN information_schema.referential_constraints r
+           ON r.constraint_schema = k.constraint_schema
+          AND r.constraint_name = k.constraint_name
+          AND r.table_name = k.table_name
+         WHERE k.referenced_table_name IS NOT NULL
+           AND ((k.table_schema = ? AND k.table_name = ?)
+             OR (k.referenced_table_schema = ? AND k.referenced_table_name = ?))
+         ORDER BY k.table_schema, k.table_name, k.constraint_name, k.ordinal_position",
+    )
+    .bind(database)
+    .bind(table)
+    .bind(database)
+    .bind(table)
+    .fetch_all(pool)
+    .await?;
+    let mut foreign_keys: Vec<ForeignKeyInfo> = vec![];
+    for row in key_columns {
+        // tables of the same database are shown without it
+        let table_name = |schema: &str, name: &str| -> Result<String> {
+            let schema: String = row.try_get(schema)?;
+            let name: String = row.try_get(name)?;
+            Ok(if schema == database {
+                name
+            } else {
+                format!("{}.{}", schema, name)
+            })
+        };
+        let name: String = row.try_get("name")?;
+        let from_table = table_name("from_schema", "from_table")?;
+        let from_column: String = row.try_get("from_column")?;
+        let to_column: String = row.try_get("to_column")?;
+        match foreign_keys
+            .last_mut()
+            .filter(|key| key.name == name && key.from_table == from_table)
+        {
+            Some(key) => {
+                key.from_columns.push(from_column);
+                key.to_columns.push(to_column);
+            }
+            None => foreign_keys.push(ForeignKeyInfo {
+                name,
+                from_table,
+                from_columns: vec![from_column],
+                to_table: table_name("to_schema", "to_table")?,
+                to_columns: vec![to_column],
+                on_update: row.try_get("on_update")?,
+                on_delete: row.try_get("on_delete")?,
+            }),
+        }
+    }
+
+    let mut keys = TableKeys {
+        indexes,
+        constraints,
+        ..Default::default()
+    };
+    for key in foreign_keys {
+        if key.to_table == table {
+            keys.incoming.push(key.clone());
+        }
+        if key.from_table == table {
+            keys.outgoing.push(key);
+        }
+    }
+    Ok(keys)
+}
+
+/// SQLite keeps check constraints only in the `CREATE TABLE` statement, they
+/// are not listed.
+async fn sqlite_keys(pool: &SqlitePool, database: &str, table: &str) -> Result<TableKeys> {
+    let schema = quote_identifier(database);
+
+    let index_list = sqlx::query(&format!(
+        "SELECT l.name, l.\"unique\", l.origin, m.sql
+         FROM pragma_index_list(?1, ?2) l
+         LEFT JOIN {}.sqlite_master m ON m.type = 'index' AND m.name = l.name
+         ORDER BY l.origin = 'pk' DESC, l.name",
+        schema
+    ))
+    .bind(table)
+    .bind(database)
+    .fetch_all(pool)
+    .await?;
+    // same as the table sizes, dbstat may be missing from other SQLite builds
+    let sizes: HashMap<String, i64> =
+        sqlx::query_as("SELECT name, SUM(pgsize) FROM dbstat WHERE schema = ? GROUP BY name")
+            .bind(database)
+            .fetch_all(pool)
+            .await
+            .map(|sizes| sizes.into_iter().collect())
+            .unwrap_or_default();
+
+    let mut keys = TableKeys::default();
+    for row in index_list {
+        let name: String = row.try_get("name")?;
+        let origin: String = row.try_get("origin")?;
+        let sql: Option<String> = row.try_get("sql")?;
+        let columns: Vec<Option<String>> =
+            sqlx::query_scalar("SELECT name FROM pragma_index_info(?, ?) ORDER BY seqno")
+                .bind(&name)
+                .bind(database)
+                .fetch_all(pool)
+                .await?;
+        let columns: Vec<String> = columns
+            .into_iter()
+            .map(|column| column.unwrap_or_else(|| "(expression)".to_string()))
+            .collect();
+        let unique: bool = row.try_get("unique")?;
+        let primary = origin == "pk";
+        if primary || origin == "u" {
+            let kind = if primary {
+                ConstraintKind::PrimaryKey
+            } else {
+                ConstraintKind::Unique
+            };
+            keys.constraints.push(ConstraintInfo {
+                // auto indexes are named after the table, not by the user
+                name: String::new(),
+                kind,
+                definition: kind.definition(&columns),
+            });
+        }
+        keys.indexes.push(IndexInfo {
+            size: sizes.get(&name).copied(),
+            name,
+            columns,
+            unique,
+            primary,
+            method: None,
+            predicate: sql.as_deref().and_then(partial_index_predicate),
+        });
+    }
+
+    // an INTEGER PRIMARY KEY is the rowid and has no index of its own
+    if !keys.indexes.iter().any(|index| index.primary) {
+        let primary_key: Vec<String> =
+            sqlx::query_scalar("SELECT name FROM pragma_table_info(?, ?) WHERE pk > 0 ORDER BY pk")
+                .bind(table)
+                .bind(database)
+                .fetch_all(pool)
+                .await?;
+        if !primary_key.is_empty() {
+            keys.constraints.insert(
+                0,
+                ConstraintInfo {
+                    name: String::new(),
+                    kind: ConstraintKind::PrimaryKey,
+                    definition: ConstraintKind::PrimaryKey.definition(&primary_key),
+                },
+            );
+        }
+    }
+
+    let outgoing = sqlx::query(
+        "SELECT id, \"table\", \"from\", \"to\", on_update, on_delete
+         FROM pragma_foreign_key_list(?, ?)
+         ORDER BY id, seq",
+    )
+    .bind(table)
+    .bind(database)
+    .fetch_all(pool)
+    .await?;
+    keys.outgoing = sqlite_foreign_keys(outgoing, Some(table))?;
+
+    let incoming = sqlx::query(&format!(
+        "SELECT m.name AS from_table, f.id, f.\"table\", f.\"from\", f.\"to\",
+                f.on_update, f.on_delete
+         FROM {}.sqlite_master m
+         JOIN pragma_foreign_key_list(m.name, ?1) f
+         WHERE m.type = 'table' AND f.\"table\" = ?2 COLLATE NOCASE
+         ORDER BY m.name, f.id, f.seq",
+        schema
+    ))
+    .bind(database)
+    .bind(table)
+    .fetch_all(pool)
+    .await?;
+    keys.incoming = sqlite_foreign_keys(incoming, None)?;
+    Ok(keys)
+}
+
+/// Groups the rows of `pragma_foreign_key_list` into foreign keys. The keys
+/// start at `from_table`, or at the table in the `from_table` column.
+fn sqlite_foreign_keys(
+    rows: Vec<SqliteRow>,
+    from_table: Option<&str>,
+) -> Result<Vec<ForeignKeyInfo>> {
+    let mut keys: Vec<ForeignKeyInfo> = vec![];
+    let mut last_id = None;
+    for row in rows {
+        let id: i64 = row.try_get("id")?;
+        let from_table: String = match from_table {
+            Some(table) => table.to_string(),
+            None => row.try_get("from_table")?,
+        };
+        let from: String = row.try_get("from")?;
+        let to: Option<String> = row.try_get("to")?;
+        if last_id == Some((from_table.clone(), id)) {
+            if let Some(key) = keys.last_mut() {
+                key.from_columns.push(from);
+                key.to_columns.extend(to);
+            }
+            continue;
+        }
+        last_id = Some((from_table.clone(), id));
+        keys.push(ForeignKeyInfo {
+            name: String::new(),
+            from_table,
+            from_columns: vec![from],
+            to_table: row.try_get("table")?,
+            to_columns: to.into_iter().collect(),
+            on_update: row.try_get("on_update")?,
+            on_delete: row.try_get("on_delete")?,
+        });
+    }
+    Ok(keys)
+}
+
+/// The `WHERE` clause of a `CREATE INDEX` statement.
+fn partial_index_predicate(sql: &str) -> Option<String> {
+    // ASCII only, so positions stay the same in `sql`
+    let position = sql
+        .to_ascii_uppercase()
+        .replace(['\n', '\r', '\t'], " ")
+        .rfind(" WHERE ")?;
+    Some(sql[position + " WHERE ".len()..].trim().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        db::{catalog::TableKind, connection::Connection},
+        tabs::db_types_tab::DBTypes,
+    };
+    use pretty_assertions::assert_eq;
+
+    fn table(name: &str) -> TableInfo {
+        TableInfo {
+            schema: None,
+            name: name.to_string(),
+            kind: TableKind::Table,
+            rows: None,
+            size: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_sqlite_table_keys() {
+        let connection = Connection {
+            db_type: DBTypes::SQLITE,
+            database: ":memory:".to_string(),
+            ..Default::default()
+        };
+        let pool = DbPool::connect(&connection, false).await.unwrap();
+        pool.execute(
+            "CREATE TABLE customers (id INTEGER PRIMARY KEY, email TEXT UNIQUE);
+             CREATE TABLE orders (
+                 id INTEGER PRIMARY KEY,
+                 customer_id INTEGER REFERENCES customers (id) ON DELETE CASCADE,
+                 status TEXT
+             );
+             CREATE INDEX orders_open ON orders (customer_id) WHERE status = 'open';",
+        )
+        .await
+        .unwrap();
+
+        let keys = table_keys(&pool, "main", &table("orders")).await.unwrap();
+        assert_eq!(
+            keys.indexes
+                .iter()
+                .map(|index| (index.name.as_str(), index.predicate.as_deref()))
+                .collect::<Vec<_>>(),
+            vec![("orders_open", Some("status = 'open'"))]
+        );
+        assert_eq!(
+            keys.constraints,
+            vec![ConstraintInfo {
+                name: String::new(),
+                kind: ConstraintKind::PrimaryKey,
+                definition: "PRIMARY KEY (id)".to_string(),
+            }]
+        );
+        assert_eq!(
+            keys.outgoing,
+            vec![ForeignKeyInfo {
+                name: String::new(),
+                from_table: "orders".to_string(),
+                from_columns: vec!["customer_id".to_string()],
+                to_table: "customers".to_string(),
+                to_columns: vec!["id".to_string()],
+                on_update: "NO ACTION".to_string(),
+                on_delete: "CASCADE".to_string(),
+            }]
+        );
+        assert!(keys.incoming.is_empty());
+
+        let keys = table_keys(&pool, "main", &table("customers"))
+            .await
+            .unwrap();
+        assert_eq!(keys.constraints.len(), 2);
+        assert_eq!(keys.constraints[1].kind, ConstraintKind::Unique);
+        assert_eq!(keys.constraints[1].definition, "UNIQUE (email)");
+        assert!(keys.indexes[0].unique);
+        assert_eq!(keys.incoming.len(), 1);
+        assert_eq!(keys.incoming[0].from_table, "orders");
+        pool.close().await;
+    }
+
+    #[test]
+    fn test_partial_index_predicate() {
+        assert_eq!(
+            partial_index_predicate("CREATE INDEX i ON t (a)\n  where deleted_at IS NULL")
+                .as_deref(),
+            Some("deleted_at IS NULL")
+        );
+        assert_eq!(partial_index_predicate("CREATE INDEX i ON t (a)"), None);
+    }
+}
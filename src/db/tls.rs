@@ -0,0 +1,232 @@
+use std::collections::BTreeMap;
+
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+use sqlx::{
+    mysql::{MySqlConnectOptions, MySqlSslMode},
+    postgres::{PgConnectOptions, PgSslMode},
+};
+
+use super::ssh_tunnel::expand_home;
+use crate::tabs::db_types_tab::DBTypes;
+
+/// TLS negotiation, named after the libpq `sslmode` values.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SslMode {
+    Disable,
+    /// Use TLS when the server supports it.
+    #[default]
+    Prefer,
+    Require,
+    /// Require TLS and check the server certificate against the CA.
+    VerifyCa,
+    /// Like `VerifyCa`, and also check that the host name matches the certificate.
+    VerifyFull,
+}
+
+impl SslMode {
+    pub const ALL: [SslMode; 5] = [
+        SslMode::Disable,
+        SslMode::Prefer,
+        SslMode::Require,
+        SslMode::VerifyCa,
+        SslMode::VerifyFull,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SslMode::Disable => "disable",
+            SslMode::Prefer => "prefer",
+            SslMode::Require => "require",
+            SslMode::VerifyCa => "verify-ca",
+            SslMode::VerifyFull => "verify-full",
+        }
+    }
+
+    /// Accepts both the Postgres (`verify-full`) and MySQL (`VERIFY_IDENTITY`) spellings.
+    pub fn parse(value: &str) -> Result<Self> {
+        let mode = match value.to_ascii_lowercase().replace('_', "-").as_str() {
+            "disable" | "disabled" => SslMode::Disable,
+            "allow" | "prefer" | "preferred" => SslMode::Prefer,
+            "require" | "required" => SslMode::Require,
+            "verify-ca" => SslMode::VerifyCa,
+            "verify-full" | "verify-identity" => SslMode::VerifyFull,
+            _ => bail!("unsupported ssl mode '{}'", value),
+        };
+        Ok(mode)
+    }
+
+    /// The `ssl-mode` spelling used by MySQL and MariaDB.
+    pub fn as_mysql_str(&self) -> &'static str {
+        match self {
+            SslMode::Disable => "DISABLED",
+            SslMode::Prefer => "PREFERRED",
+            SslMode::Require => "REQUIRED",
+            SslMode::VerifyCa => "VERIFY_CA",
+            SslMode::VerifyFull => "VERIFY_IDENTITY",
+        }
+    }
+
+    pub fn next(&self) -> Self {
+        let index = Self::ALL.iter().position(|mode| mode == self).unwrap_or(0);
+        Self::ALL[(index + 1) % Self::ALL.len()]
+    }
+
+    pub fn previous(&self) -> Self {
+        let index = Self::ALL.iter().position(|mode| mode == self).unwrap_or(0);
+        Self::ALL[(index + Self::ALL.len() - 1) % Self::ALL.len()]
+    }
+}
+
+/// TLS settings of a connection. Empty paths are not passed on to the driver.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TlsConfig {
+    pub mode: SslMode,
+    /// CA bundle used to verify the server certificate.
+    pub ca_file: String,
+    /// Client certificate and key for certificate authentication.
+    pub client_cert: String,
+    pub client_key: String,
+}
+
+impl TlsConfig {
+    /// Moves TLS related URL query parameters (`sslmode`, `sslrootcert`, ... for
+    /// Postgres, `ssl-mode`, `ssl-ca`, ... for MySQL) out of `params`.
+    pub fn take_from_params(params: &mut BTreeMap<String, String>) -> Result<Self> {
+        let mut tls = TlsConfig::default();
+        for key in ["sslmode", "ssl-mode"] {
+            if let Some(value) = params.remove(key) {
+                tls.mode = SslMode::parse(&value)?;
+            }
+        }
+        for key in ["sslrootcert", "ssl-ca"] {
+            if let Some(value) = params.remove(key) {
+                tls.ca_file = value;
+            }
+        }
+        for key in ["sslcert", "ssl-cert"] {
+            if let Some(value) = params.remove(key) {
+                tls.client_cert = value;
+            }
+        }
+        for key in ["sslkey", "ssl-key"] {
+            if let Some(value) = params.remove(key) {
+                tls.client_key = value;
+            }
+        }
+        Ok(tls)
+    }
+
+    /// The URL query parameters for the non-default settings, the inverse of
+    /// `take_from_params`.
+    pub fn to_params(&self, db_type: DBTypes) -> Vec<(&'static str, String)> {
+        let (mode_key, mode, ca_key, cert_key, key_key) = match db_type {
+            DBTypes::MYSQL | DBTypes::MARIA => (
+                "ssl-mode",
+                self.mode.as_mysql_str(),
+                "ssl-ca",
+                "ssl-cert",
+                "ssl-key",
+            ),
+            _ => (
+                "sslmode",
+                self.mode.as_str(),
+                "sslrootcert",
+                "sslcert",
+                "sslkey",
+            ),
+        };
+
+        let mut params = vec![];
+        if self.mode != SslMode::default() {
+            params.push((mode_key, mode.to_string()));
+        }
+        for (key, value) in [
+            (ca_key, &self.ca_file),
+            (cert_key, &self.client_cert),
+            (key_key, &self.client_key),
+        ] {
+            if !value.is_empty() {
+                params.push((key, value.clone()));
+            }
+        }
+        params
+    }
+
+    pub fn apply_pg(&self, mut options: PgConnectOptions) -> PgConnectOptions {
+        options = options.ssl_mode(match self.mode {
+            SslMode::Disable => PgSslMode::Disable,
+            SslMode::Prefer => PgSslMode::Prefer,
+            SslMode::Require => PgSslMode::Require,
+            SslMode::VerifyCa => PgSslMode::VerifyCa,
+            SslMode::VerifyFull => PgSslMode::VerifyFull,
+        });
+        if !self.ca_file.is_empty() {
+            options = options.ssl_root_cert(expand_home(&self.ca_file));
+        }
+        if !self.client_cert.is_empty() {
+            options = options.ssl_client_cert(expand_home(&self.client_cert));
+        }
+        if !self.client_key.is_empty() {
+            options = options.ssl_client_key(expand_home(&self.client_key));
+        }
+        options
+    }
+
+    pub fn apply_mysql(&self, mut options: MySqlConnectOptions) -> MySqlConnectOptions {
+        options = options.ssl_mode(match self.mode {
+            SslMode::Disable => MySqlSslMode::Disabled,
+            SslMode::Prefer => MySqlSslMode::Preferred,
+            SslMode::Require => MySqlSslMode::Required,
+            SslMode::VerifyCa => MySqlSslMode::VerifyCa,
+            SslMode::VerifyFull => MySqlSslMode::VerifyIdentity,
+        });
+        if !self.ca_file.is_empty() {
+            options = options.ssl_ca(expand_home(&self.ca_file));
+        }
+        if !self.client_cert.is_empty() {
+            options = options.ssl_client_cert(expand_home(&self.client_cert));
+        }
+        if !self.client_key.is_empty() {
+            options = options.ssl_client_key(expand_home(&self.client_key));
+        }
+        options
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_take_from_params() {
+        let mut params = BTreeMap::from([
+            ("ssl-mode".to_string(), "VERIFY_IDENTITY".to_string()),
+            ("ssl-ca".to_string(), "/etc/ssl/ca.pem".to_string()),
+            ("charset".to_string(), "utf8mb4".to_string()),
+        ]);
+
+        let tls = TlsConfig::take_from_params(&mut params).unwrap();
+        assert_eq!(
+            tls,
+            TlsConfig {
+                mode: SslMode::VerifyFull,
+                ca_file: "/etc/ssl/ca.pem".to_string(),
+                ..Default::default()
+            }
+        );
+        assert_eq!(params.keys().collect::<Vec<_>>(), vec!["charset"]);
+        assert_eq!(
+            tls.to_params(DBTypes::MARIA),
+            vec![
+                ("ssl-mode", "VERIFY_IDENTITY".to_string()),
+                ("ssl-ca", "/etc/ssl/ca.pem".to_string())
+            ]
+        );
+
+        let mut params = BTreeMap::from([("sslmode".to_string(), "sometimes".to_string())]);
+        assert!(TlsConfig::take_from_params(&mut params).is_err());
+    }
+}
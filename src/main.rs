@@ -1,6 +1,16 @@
-use std::{io, sync::mpsc};
+use std::{
+    cell::RefCell,
+    future::Future,
+    io,
+    rc::Rc,
+    sync::mpsc,
+    time::{Duration, Instant},
+};
+
+use anyhow::anyhow;
+use args::{CliArgs, StartConnection};
 
-use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind};
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
 use log::*;
 use ratatui::{
     prelude::*,
@@ -8,20 +18,57 @@ use ratatui::{
 };
 use tui_logger::*;
 mod args;
+mod db;
 mod tabs;
 mod tui;
 mod ui;
 
 use crate::tabs::DBTab;
+use db::{
+    catalog::{
+        self, ColumnInfo, Loadable, OpenTable, SchemaInfo, SessionCatalog, SharedCatalog, TableInfo,
+    },
+    connection::{Connection, Environment, PasswordSource},
+    credentials::CredentialStore,
+    health::{self, HealthAction, SharedHealth},
+    import,
+    pool::{test_connection, ConnectionTestReport, DbPool, QueryResult},
+    session::{DatabasePools, Session},
+    sql::is_write_statement,
+    store::{ConnectionStore, SharedConnections},
+    table_keys::{self, TableKeys},
+};
 use tabs::db_connections_tab::*;
 use tabs::db_databases_tab::*;
+use tabs::db_schemas_tab::*;
 use tabs::db_tables_tab::*;
 use tabs::db_types_tab::*;
 
 use ui::{
-    connection_popup::DbConnectionPopup, quit_confirm_popup::QuitConfirmPopup, style::SharedTheme,
+    confirm_popup::ConfirmPopup,
+    connection_popup::DbConnectionPopup,
+    import_popup::ImportPopup,
+    input_popup::{InputPopup, InputPurpose},
+    message_popup::MessagePopup,
+    quit_confirm_popup::QuitConfirmPopup,
+    session_switcher_popup::{SessionEntry, SessionSwitcherPopup},
+    style::{SharedTheme, Theme},
     Popup,
 };
+
+/// How long to wait for terminal input before checking the event bus again,
+/// so results of background tasks show up without a key press.
+const INPUT_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// How long "test connection" waits before giving up.
+const CONNECTION_TEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+const CONNECTIONS_TAB_INDEX: usize = 1;
+const DATABASES_TAB_INDEX: usize = 2;
+const SCHEMAS_TAB_INDEX: usize = 3;
+const TABLES_TAB_INDEX: usize = 4;
+
+const READ_ONLY_BADGE: &str = " RO ";
 #[derive(Clone, Debug, PartialEq)]
 pub enum QuitState {
     None,
@@ -30,9 +77,153 @@ pub enum QuitState {
 
 #[derive(Debug)]
 pub enum AppEvent {
-    DBTypeSelected { db_type: DBTypes },
+    DBTypeSelected {
+        db_type: DBTypes,
+    },
     NewConnection,
-    ConnectionDetailsSubmitted { connection_string: String },
+    ConnectionDetailsSubmitted {
+        connection: Connection,
+    },
+    /// With `remember` the password goes to the credential store.
+    ConnectionOpened {
+        session: Session,
+        remember: bool,
+    },
+    ConnectionFailed {
+        connection: Connection,
+        error: String,
+    },
+    TestConnection {
+        connection: Connection,
+        /// Identifies the test in the popup, see [`AppEvent::ConnectionTested`].
+        generation: u64,
+    },
+    ConnectionTested {
+        generation: u64,
+        result: Result<ConnectionTestReport, String>,
+    },
+    Connect {
+        id: u32,
+    },
+    EditConnection {
+        id: u32,
+    },
+    ConfirmDeleteConnection {
+        id: u32,
+    },
+    DeleteConnection {
+        id: u32,
+    },
+    DuplicateConnection {
+        id: u32,
+    },
+    StartRenameConnection {
+        id: u32,
+    },
+    RenameConnection {
+        id: u32,
+        name: String,
+    },
+    UnlockCredentials {
+        passphrase: String,
+    },
+    /// A new master passphrase, asked for a second time before the store is created.
+    PassphraseEntered {
+        passphrase: String,
+    },
+    CreateCredentials {
+        passphrase: String,
+    },
+    /// The unlock or create popup was closed, whatever waited for it is dropped.
+    CredentialsDismissed,
+    ConnectWithPassword {
+        id: u32,
+        password: String,
+    },
+    /// `confirmed` is set once a production write has been confirmed.
+    ExecuteSql {
+        sql: String,
+        confirmed: bool,
+    },
+    SqlExecuted {
+        result: Result<u64, String>,
+    },
+    /// Looks for connections in `~/.pgpass`, `~/.pg_service.conf` and `~/.my.cnf`.
+    DiscoverImports,
+    ImportConnections {
+        connections: Vec<Connection>,
+    },
+    OpenSessionSwitcher,
+    /// Makes session `id` the active one.
+    SwitchSession {
+        id: u32,
+    },
+    CloseSession {
+        id: u32,
+    },
+    /// Reopens the session of saved connection `id`, or connects if it is not open.
+    Reconnect {
+        id: u32,
+    },
+    HealthChecked {
+        id: u32,
+        result: Result<Duration, String>,
+    },
+    /// Lists the databases of the active session again.
+    LoadDatabases,
+    DatabasesLoaded {
+        session: u32,
+        result: Result<Vec<catalog::DatabaseInfo>, String>,
+    },
+    /// Shows the schemas (Postgres) or the tables of database `name` of the
+    /// active session.
+    SelectDatabase {
+        name: String,
+    },
+    /// Lists the schemas of the selected database again.
+    LoadSchemas,
+    SchemasLoaded {
+        session: u32,
+        database: String,
+        result: Result<Vec<SchemaInfo>, String>,
+    },
+    /// Shows the tables of schema `name` of the selected database.
+    SelectSchema {
+        name: String,
+    },
+    /// Lists the tables of the selected database (and schema) again.
+    LoadTables,
+    TablesLoaded {
+        session: u32,
+        database: String,
+        schema: Option<String>,
+        result: Result<Vec<TableInfo>, String>,
+    },
+    /// Shows the rows and the structure of `table` in the Tables tab.
+    OpenTable {
+        table: TableInfo,
+    },
+    TableDataLoaded {
+        session: u32,
+        table: TableInfo,
+        result: Result<QueryResult, String>,
+    },
+    TableColumnsLoaded {
+        session: u32,
+        table: TableInfo,
+        result: Result<Vec<ColumnInfo>, String>,
+    },
+    TableKeysLoaded {
+        session: u32,
+        table: TableInfo,
+        result: Result<TableKeys, String>,
+    },
+    /// Goes back from the rows of a table to the list of tables.
+    CloseTable,
+    Reconnected {
+        id: u32,
+        result: Result<Session, String>,
+    },
     CancelClosePopup,
     ConfirmQuitApp,
 }
@@ -41,9 +232,24 @@ pub struct App<'a> {
     title: String,
     do_quit: QuitState,
     theme: SharedTheme,
+    connections: SharedConnections,
+    credentials: CredentialStore,
+    /// Password waiting to be written once the credential store is unlocked or created.
+    pending_secret: Option<(u32, String)>,
+    /// Saved connection to open once the credential store is unlocked.
+    pending_connect: Option<u32>,
     tabs: Vec<Box<dyn DBTab>>,
     current_tab_index: usize,
     db_type: Option<DBTypes>,
+    /// Open connections, each with its own pool.
+    sessions: Vec<Session>,
+    /// Index into `sessions` of the one the tabs and `:` work on.
+    active_session: usize,
+    next_session_id: u32,
+    health: SharedHealth,
+    catalog: SharedCatalog,
+    /// `--read-only`: every session is opened read-only.
+    force_read_only: bool,
     event_bus: mpsc::Sender<AppEvent>,
     show_logs_window: bool,
     popup_stack: Vec<Popup<'a>>,
@@ -51,21 +257,42 @@ pub struct App<'a> {
 }
 
 impl App<'_> {
-    pub fn new(sender: mpsc::Sender<AppEvent>) -> Self {
+    pub fn new(sender: mpsc::Sender<AppEvent>, cli_args: CliArgs) -> Self {
+        let theme: SharedTheme = Rc::new(Theme::init(&cli_args.theme));
+        let connections = Rc::new(RefCell::new(ConnectionStore::load_default()));
+        let credentials = CredentialStore::load_default();
+        let health = SharedHealth::default();
+        let catalog = SharedCatalog::default();
+        let mut popup_stack = vec![];
+        if credentials.is_initialized() {
+            popup_stack.push(Popup::Input(Box::new(unlock_credentials_popup())));
+        }
+
         Self {
             title: " Database Manager ".to_string(),
             do_quit: QuitState::None,
-            theme: SharedTheme::default(),
+            theme: theme.clone(),
+            connections: connections.clone(),
+            credentials,
+            pending_secret: None,
+            pending_connect: None,
             tabs: vec![
                 Box::new(DbTypesTab::default()),
-                Box::new(DbConnectionsTab::default()),
-                Box::new(DbDatabasesTab::default()),
-                Box::new(DbTablesTab::default()),
+                Box::new(DbConnectionsTab::new(connections, health.clone(), theme)),
+                Box::new(DbDatabasesTab::new(catalog.clone())),
+                Box::new(DbSchemasTab::new(catalog.clone())),
+                Box::new(DbTablesTab::new(catalog.clone())),
             ],
             current_tab_index: 0,
             db_type: None,
+            sessions: vec![],
+            active_session: 0,
+            next_session_id: 1,
+            health,
+            catalog,
+            force_read_only: cli_args.read_only,
             event_bus: sender,
-            popup_stack: vec![],
+            popup_stack,
             show_logs_window: false,
             tui_widget_state: TuiWidgetState::new().set_default_display_level(LevelFilter::Debug),
         }
@@ -73,6 +300,53 @@ impl App<'_> {
     pub fn has_popup(&self) -> bool {
         !self.popup_stack.is_empty()
     }
+
+    /// Connects to what was given on the command line, straight into its Databases tab.
+    pub fn connect_on_start(&mut self, start: StartConnection) -> anyhow::Result<()> {
+        match start {
+            StartConnection::Url(connection) => {
+                debug!(
+                    "Connecting to {} from the command line",
+                    connection.redacted()
+                );
+                self.db_type = Some(connection.db_type);
+                self.current_tab_index = CONNECTIONS_TAB_INDEX;
+                self.open_connection(*connection, false);
+            }
+            StartConnection::Saved(name) => {
+                let connection = {
+                    let store = self.connections.borrow();
+                    let connections = store.connections();
+                    connections
+                        .iter()
+                        .find(|connection| connection.name == name)
+                        .or_else(|| {
+                            connections
+                                .iter()
+                                .find(|connection| connection.name.eq_ignore_ascii_case(&name))
+                        })
+                        .map(|connection| (connection.id, connection.db_type))
+                };
+                let Some((id, db_type)) = connection else {
+                    return Err(anyhow!("no saved connection named \"{}\"", name));
+                };
+
+                self.db_type = Some(db_type);
+                self.current_tab_index = CONNECTIONS_TAB_INDEX;
+                if !self.credentials.is_unlocked() && self.credentials.has_password(id) {
+                    // the unlock popup is already open, connect once it is done
+                    self.pending_connect = Some(id);
+                } else {
+                    self.connect_saved(id);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn active_session(&self) -> Option<&Session> {
+        self.sessions.get(self.active_session)
+    }
 }
 
 impl App<'_> {
@@ -95,12 +369,17 @@ impl App<'_> {
             }
             self.handle_events()?;
 
+            self.check_health();
             self.check_event_loop(&rx);
         }
         Ok(())
     }
 
     fn handle_events(&mut self) -> io::Result<()> {
+        if !event::poll(INPUT_POLL_INTERVAL)? {
+            return Ok(());
+        }
+
         match event::read()? {
             Event::Key(key_event) if key_event.kind == KeyEventKind::Press => {
                 if self.has_popup() {
@@ -113,11 +392,24 @@ impl App<'_> {
                     self.handle_key_event_main_app(key_event)
                 }
             }
+            Event::Paste(text) => {
+                if let Some(popup) = self.popup_stack.last_mut() {
+                    popup.handle_paste(&text);
+                } else if !self.show_logs_window {
+                    self.tabs[self.current_tab_index].handle_paste(&text);
+                }
+                Ok(())
+            }
             _ => Ok(()),
         }
     }
 
     fn handle_key_event_main_app(&mut self, key_event: KeyEvent) -> io::Result<()> {
+        let current_tab = &mut self.tabs[self.current_tab_index];
+        if current_tab.handles_key(&key_event) {
+            return current_tab.handle_input(key_event, &self.event_bus);
+        }
+
         match key_event.code {
             KeyCode::Char('q') | KeyCode::Esc => {
                 let quit_popup = QuitConfirmPopup::new();
@@ -132,6 +424,20 @@ impl App<'_> {
                 }
             }
             KeyCode::F(12) => self.show_logs_window = !self.show_logs_window,
+            KeyCode::Char(':') => {
+                if let Some(session) = self.active_session() {
+                    let title = if session.read_only {
+                        " Execute SQL (read-only) "
+                    } else {
+                        " Execute SQL "
+                    };
+                    let popup = InputPopup::new(title, "", InputPurpose::ExecuteSql);
+                    self.popup_stack.push(Popup::Input(Box::new(popup)));
+                }
+            }
+            KeyCode::Char('p') if key_event.modifiers.contains(KeyModifiers::CONTROL) => {
+                self.event_bus.send(AppEvent::OpenSessionSwitcher).unwrap();
+            }
             _ => {
                 let current_tab = &mut self.tabs[self.current_tab_index];
                 current_tab.handle_input(key_event, &self.event_bus)?;
@@ -143,14 +449,7 @@ impl App<'_> {
 
     fn handle_popup_input(&mut self, key_event: &KeyEvent) -> io::Result<()> {
         if let Some(popup) = self.popup_stack.last_mut() {
-            match popup {
-                Popup::Connection(popup) => {
-                    popup.handle_input(key_event, &self.event_bus)?;
-                }
-                Popup::Quit(popup) => {
-                    popup.handle_input(key_event, &self.event_bus)?;
-                }
-            }
+            popup.handle_input(key_event, &self.event_bus)?;
         }
         Ok(())
     }
@@ -228,29 +527,41 @@ impl App<'_> {
         // draw current tab index
         let _ = self.tabs[self.current_tab_index].draw(frame, main_body_chunk);
 
-        frame.render_widget(
-            Paragraph::new(self.current_tab_index.to_string()),
-            bottom_chunk,
-        );
+        self.draw_status_bar(frame, bottom_chunk);
 
-        if self.has_popup() {
-            if let Some(popup) = self.popup_stack.last_mut() {
-                match popup {
-                    Popup::Connection(popup) => {
-                        popup.render_widget(frame, frame.size());
-                    }
-                    Popup::Quit(popup) => {
-                        popup.render_widget(frame, frame.size());
-                    }
-                }
-            };
+        if let Some(popup) = self.popup_stack.last_mut() {
+            popup.render_widget(frame, frame.size());
         }
         Ok(())
     }
 
+    /// Shows the active connection and paints the bar in the colour of its environment.
     fn draw_title(&self, f: &mut Frame, r: Rect) {
-        let title = Title::from(self.title.clone()).alignment(Alignment::Center);
-        f.render_widget(Block::default().borders(Borders::TOP).title(title), r);
+        let Some(session) = self.active_session() else {
+            let title = Title::from(self.title.clone()).alignment(Alignment::Center);
+            f.render_widget(Block::default().borders(Borders::TOP).title(title), r);
+            return;
+        };
+
+        let connection = &session.connection;
+        let mut text = format!("{}- {} ", self.title, connection.name);
+        if connection.environment != Environment::None {
+            text.push_str(&format!(
+                "[{}] ",
+                connection.environment.label().to_uppercase()
+            ));
+        }
+        if session.read_only {
+            text.push_str("[RO] ");
+        }
+        let title = Title::from(text).alignment(Alignment::Center);
+        f.render_widget(
+            Block::default()
+                .borders(Borders::TOP)
+                .title(title)
+                .style(self.theme.environment_bar(&connection.environment)),
+            r,
+        );
     }
 
     fn draw_top_bar(&self, f: &mut Frame, r: Rect) {
@@ -259,17 +570,11 @@ impl App<'_> {
             horizontal: 1,
         });
 
-        let tab_labels: Vec<Span> = self
-            .tabs
-            .iter()
-            .map(|tab| Span::raw(tab.get_title()))
-            .collect();
-
         let divider = " | ";
         let tabs: Vec<Line> = self
             .tabs
             .iter()
-            // .enumerate()
+            .filter(|tab| !tab.is_hidden())
             .map(|tab| {
                 let title = tab.get_title();
                 let line = Line::from(title);
@@ -277,20 +582,87 @@ impl App<'_> {
             })
             .collect();
 
+        let mut block = Block::default();
+        if self
+            .active_session()
+            .is_some_and(|session| session.read_only)
+        {
+            block = block.title(
+                Title::from(Span::styled(READ_ONLY_BADGE, self.theme.read_only_badge()))
+                    .alignment(Alignment::Right),
+            );
+        }
+
         f.render_widget(
             Tabs::new(tabs)
                 .block(
-                    Block::default()
+                    block
                         .borders(Borders::ALL)
-                        .border_style(self.theme.block(false)),
+                        .border_style(match self.active_session() {
+                            Some(session)
+                                if session.connection.environment != Environment::None =>
+                            {
+                                self.theme.environment(&session.connection.environment)
+                            }
+                            _ => self.theme.block(false),
+                        }),
                 )
                 .highlight_style(self.theme.tab(false, true))
                 .divider(divider)
-                .select(self.current_tab_index),
+                .select(
+                    self.tabs[..self.current_tab_index]
+                        .iter()
+                        .filter(|tab| !tab.is_hidden())
+                        .count(),
+                ),
             r,
         );
     }
 
+    /// One line at the bottom: the active connection, its read-only state and
+    /// how many connections are open.
+    fn draw_status_bar(&self, f: &mut Frame, r: Rect) {
+        let Some(session) = self.active_session() else {
+            f.render_widget(
+                Paragraph::new(Span::styled(" Not connected", self.theme.block(false))),
+                r,
+            );
+            return;
+        };
+
+        let connection = &session.connection;
+        let health = &session.health;
+        let mut spans = vec![Span::styled(" \u{25CF} ", self.theme.health(health.status))];
+        spans.push(Span::raw(match health.retry_in(Instant::now()) {
+            _ if health.is_reconnecting() => "reconnecting... ".to_string(),
+            Some(wait) => format!("disconnected, retrying in {}s ", wait.as_secs() + 1),
+            None => match health.latency {
+                Some(latency) => format!("{} {}ms ", health.status.as_str(), latency.as_millis()),
+                None => format!("{} ", health.status.as_str()),
+            },
+        }));
+        if session.read_only {
+            spans.push(Span::styled(READ_ONLY_BADGE, self.theme.read_only_badge()));
+        }
+        spans.push(Span::raw(format!(
+            " {} [{}] {}",
+            connection.name,
+            connection.db_type.as_str(),
+            connection.summary()
+        )));
+        if self.sessions.len() > 1 {
+            spans.push(Span::styled(
+                format!(
+                    "  ({}/{} open, Ctrl-p: switch)",
+                    self.active_session + 1,
+                    self.sessions.len()
+                ),
+                self.theme.block(false),
+            ));
+        }
+        f.render_widget(Paragraph::new(Line::from(spans)), r);
+    }
+
     fn draw_log_window(&self, f: &mut Frame) -> io::Result<()> {
         let size = f.size();
 
@@ -314,8 +686,7 @@ impl App<'_> {
     }
 
     fn check_event_loop(&mut self, rx: &mpsc::Receiver<AppEvent>) {
-        let event_result = rx.try_recv();
-        if let Ok(event) = event_result {
+        while let Ok(event) = rx.try_recv() {
             match event {
                 AppEvent::DBTypeSelected { db_type } => {
                     // Handle database type selection
@@ -327,18 +698,317 @@ impl App<'_> {
                     // Handle new connection
                     debug!("New Connection");
                     if let Some(db_type) = &self.db_type {
-                        let popup = DbConnectionPopup::new(db_type.as_str());
-                        self.popup_stack.push(Popup::Connection(popup));
+                        let popup = DbConnectionPopup::new(*db_type);
+                        self.popup_stack.push(Popup::Connection(Box::new(popup)));
                     } else {
                         debug!(
                             "No database type selected. New Connection Entry Pop failed to opened"
                         );
                     }
                 }
-                AppEvent::ConnectionDetailsSubmitted { connection_string } => {
-                    // Handle connection details submission TODO:
-                    debug!("Connection Details Submitted: {:?}", connection_string);
+                AppEvent::Connect { id } => {
+                    if let Some(index) = self.connection_session_index(id) {
+                        debug!("Connection {} is already open, switching to it", id);
+                        self.switch_session(self.sessions[index].id);
+                    } else {
+                        debug!("Connect to saved connection {}", id);
+                        self.connect_saved(id);
+                    }
+                }
+                AppEvent::ConnectWithPassword { id, password } => {
+                    debug!("Connect to saved connection {} with entered password", id);
+                    let connection = self.connections.borrow().get(id).cloned();
+                    if let Some(mut connection) = connection {
+                        connection.password = password;
+                        self.open_connection(connection, true);
+                    }
+                }
+                AppEvent::EditConnection { id } => {
+                    debug!("Edit connection {}", id);
+                    let connection = self.connections.borrow().get(id).cloned();
+                    if let Some(mut connection) = connection {
+                        if self.credentials.is_unlocked() {
+                            connection.password = self.stored_password(id);
+                        }
+                        let popup = DbConnectionPopup::edit(&connection);
+                        self.popup_stack.push(Popup::Connection(Box::new(popup)));
+                    }
+                }
+                AppEvent::ConfirmDeleteConnection { id } => {
+                    let name = self.connections.borrow().get(id).map(|c| c.name.clone());
+                    if let Some(name) = name {
+                        self.popup_stack.push(Popup::Confirm(ConfirmPopup::new(
+                            " Delete Connection ",
+                            format!("Delete the saved connection \"{}\"?", name),
+                            AppEvent::DeleteConnection { id },
+                        )));
+                    }
+                }
+                AppEvent::DeleteConnection { id } => {
+                    debug!("Delete connection {}", id);
+                    let result = self.connections.borrow_mut().remove(id);
+                    if let Err(e) = result.and_then(|_| self.credentials.remove_password(id)) {
+                        self.show_error(" Delete Failed ", e);
+                    }
+                }
+                AppEvent::DuplicateConnection { id } => {
+                    debug!("Duplicate connection {}", id);
+                    let result = self.connections.borrow_mut().duplicate(id);
+                    let result = result.and_then(|new_id| {
+                        if self.credentials.is_unlocked() && self.credentials.has_password(id) {
+                            let password = self.stored_password(id);
+                            self.credentials.set_password(new_id, &password)?;
+                        }
+                        Ok(())
+                    });
+                    if let Err(e) = result {
+                        self.show_error(" Duplicate Failed ", e);
+                    }
+                }
+                AppEvent::StartRenameConnection { id } => {
+                    let name = self.connections.borrow().get(id).map(|c| c.name.clone());
+                    if let Some(name) = name {
+                        let popup = InputPopup::new(
+                            " Rename Connection ",
+                            &name,
+                            InputPurpose::RenameConnection { id },
+                        );
+                        self.popup_stack.push(Popup::Input(Box::new(popup)));
+                    }
+                }
+                AppEvent::RenameConnection { id, name } => {
+                    debug!("Rename connection {} to {:?}", id, name);
+                    let result = self.connections.borrow_mut().rename(id, &name);
+                    if let Err(e) = result {
+                        self.show_error(" Rename Failed ", e);
+                    }
+                }
+                AppEvent::ConnectionDetailsSubmitted { mut connection } => {
+                    debug!("Connection Details Submitted: {}", connection.redacted());
+                    // saved before connecting, so the entry is kept even if
+                    // the server can not be reached right now
+                    match self.save_connection(&mut connection) {
+                        Ok(()) => {
+                            if let Some(Popup::Connection(popup)) = self.popup_stack.last_mut() {
+                                popup.mark_saved(&connection);
+                            }
+                        }
+                        Err(e) => self.show_error(" Saving Connection Failed ", e),
+                    }
+                    self.open_connection(connection, true);
+                }
+                AppEvent::ConnectionOpened { session, remember } => {
+                    info!("Connected to {}", session.connection.name);
+                    self.on_connection_opened(session, remember);
                 }
+                AppEvent::ConnectionFailed { connection, error } => {
+                    error!("Failed to connect to {}: {}", connection.name, error);
+                    self.popup_stack.push(Popup::Message(MessagePopup::error(
+                        " Connection Failed ",
+                        error,
+                    )));
+                }
+                AppEvent::TestConnection {
+                    connection,
+                    generation,
+                } => {
+                    debug!("Testing connection: {}", connection.redacted());
+                    self.test_connection(connection, generation);
+                }
+                AppEvent::ConnectionTested { generation, result } => {
+                    debug!("Connection test finished: {:?}", result);
+                    // the popup may have been closed or edited while the test was running
+                    if let Some(Popup::Connection(popup)) = self.popup_stack.last_mut() {
+                        popup.set_test_result(generation, result);
+                    }
+                }
+                AppEvent::UnlockCredentials { passphrase } => {
+                    debug!("Unlocking credential store");
+                    match self.credentials.unlock(&passphrase) {
+                        Ok(()) => self.on_credentials_ready(),
+                        Err(e) => {
+                            self.popup_stack
+                                .push(Popup::Input(Box::new(unlock_credentials_popup())));
+                            self.show_error(" Unlock Failed ", e);
+                        }
+                    }
+                }
+                AppEvent::PassphraseEntered { passphrase } => {
+                    let popup = InputPopup::masked(
+                        " Repeat Passphrase ",
+                        InputPurpose::RepeatPassphrase { passphrase },
+                    )
+                    .with_hint("Type the passphrase again (Esc: don't store)");
+                    self.popup_stack.push(Popup::Input(Box::new(popup)));
+                }
+                AppEvent::CreateCredentials { passphrase } => {
+                    debug!("Creating credential store");
+                    match self.credentials.initialize(&passphrase) {
+                        Ok(()) => self.on_credentials_ready(),
+                        Err(e) => self.show_error(" Credential Store Failed ", e),
+                    }
+                }
+                AppEvent::CredentialsDismissed => {
+                    debug!("Credential store left locked");
+                    self.pending_secret = None;
+                    self.pending_connect = None;
+                }
+                AppEvent::ExecuteSql { sql, confirmed } => self.execute_sql(sql, confirmed),
+                AppEvent::SqlExecuted { result } => match result {
+                    Ok(rows) => {
+                        info!("Statement executed, {} rows affected", rows);
+                        self.popup_stack.push(Popup::Message(MessagePopup::info(
+                            " Executed ",
+                            format!("Statement executed, {} rows affected.", rows),
+                        )));
+                    }
+                    Err(error) => {
+                        error!("Statement failed: {}", error);
+                        self.popup_stack.push(Popup::Message(MessagePopup::error(
+                            " Statement Failed ",
+                            error,
+                        )));
+                    }
+                },
+                AppEvent::LoadDatabases => {
+                    if let Some(session) = self.active_session() {
+                        self.catalog.borrow_mut().session_mut(session.id).databases =
+                            Loadable::Loading;
+                        load_databases(session, &self.event_bus);
+                    }
+                }
+                AppEvent::DatabasesLoaded { session, result } => {
+                    if let Err(error) = &result {
+                        error!("Failed to list databases: {}", error);
+                    }
+                    self.catalog.borrow_mut().session_mut(session).databases =
+                        Loadable::from_result(result);
+                }
+                AppEvent::SelectDatabase { name } => self.select_database(name),
+                AppEvent::LoadSchemas => self.load_schemas(),
+                AppEvent::SchemasLoaded {
+                    session,
+                    database,
+                    result,
+                } => {
+                    if let Err(error) = &result {
+                        error!("Failed to list the schemas of {}: {}", database, error);
+                    }
+                    let mut catalog = self.catalog.borrow_mut();
+                    let session = catalog.session_mut(session);
+                    if session.selected_database.as_ref() == Some(&database) {
+                        session.schemas = Loadable::from_result(result);
+                    }
+                }
+                AppEvent::SelectSchema { name } => self.select_schema(name),
+                AppEvent::LoadTables => self.load_tables(),
+                AppEvent::TablesLoaded {
+                    session,
+                    database,
+                    schema,
+                    result,
+                } => {
+                    if let Err(error) = &result {
+                        error!("Failed to list the tables of {}: {}", database, error);
+                    }
+                    let mut catalog = self.catalog.borrow_mut();
+                    let session = catalog.session_mut(session);
+                    // another database or schema may have been selected in the meantime
+                    if session.selected_database.as_ref() == Some(&database)
+                        && session.selected_schema == schema
+                    {
+                        session.tables = Loadable::from_result(result);
+                    }
+                }
+                AppEvent::OpenTable { table } => self.open_table(table),
+                AppEvent::TableDataLoaded {
+                    session,
+                    table,
+                    result,
+                } => {
+                    if let Err(error) = &result {
+                        error!("Failed to read {}: {}", table.name, error);
+                    }
+                    let mut catalog = self.catalog.borrow_mut();
+                    if let Some(open_table) = catalog
+                        .session_mut(session)
+                        .open_table
+                        .as_mut()
+                        .filter(|open_table| open_table.table == table)
+                    {
+                        open_table.data = Loadable::from_result(result);
+                    }
+                }
+                AppEvent::TableColumnsLoaded {
+                    session,
+                    table,
+                    result,
+                } => {
+                    if let Err(error) = &result {
+                        error!("Failed to read the columns of {}: {}", table.name, error);
+                    }
+                    let mut catalog = self.catalog.borrow_mut();
+                    if let Some(open_table) = catalog
+                        .session_mut(session)
+                        .open_table
+                        .as_mut()
+                        .filter(|open_table| open_table.table == table)
+                    {
+                        open_table.columns = Loadable::from_result(result);
+                    }
+                }
+                AppEvent::TableKeysLoaded {
+                    session,
+                    table,
+                    result,
+                } => {
+                    if let Err(error) = &result {
+                        error!("Failed to read the indexes of {}: {}", table.name, error);
+                    }
+                    let mut catalog = self.catalog.borrow_mut();
+                    if let Some(open_table) = catalog
+                        .session_mut(session)
+                        .open_table
+                        .as_mut()
+                        .filter(|open_table| open_table.table == table)
+                    {
+                        open_table.keys = Loadable::from_result(result);
+                    }
+                }
+                AppEvent::CloseTable => {
+                    if let Some(session) = self.active_session() {
+                        self.catalog.borrow_mut().session_mut(session.id).open_table = None;
+                    }
+                }
+                AppEvent::DiscoverImports => self.discover_imports(),
+                AppEvent::ImportConnections { connections } => {
+                    info!("Importing {} connections", connections.len());
+                    let mut store = self.connections.borrow_mut();
+                    let result: anyhow::Result<()> = connections
+                        .into_iter()
+                        .try_for_each(|connection| store.add(connection).map(|_| ()));
+                    drop(store);
+                    if let Err(e) = result {
+                        self.show_error(" Import Failed ", e);
+                    }
+                }
+                AppEvent::OpenSessionSwitcher => self.open_session_switcher(),
+                AppEvent::SwitchSession { id } => self.switch_session(id),
+                AppEvent::CloseSession { id } => self.close_session(id),
+                AppEvent::Reconnect { id } => self.reconnect(id),
+                AppEvent::HealthChecked { id, result } => {
+                    if let Some(index) = self.session_index(id) {
+                        let session = &mut self.sessions[index];
+                        if let Err(error) = &result {
+                            warn!(
+                                "Health check of {} failed: {}",
+                                session.connection.name, error
+                            );
+                        }
+                        session.health.record_ping(result, Instant::now());
+                    }
+                }
+                AppEvent::Reconnected { id, result } => self.on_reconnected(id, result),
                 AppEvent::CancelClosePopup => {
                     // Handle cancel/close popup
                     debug!(
@@ -354,16 +1024,712 @@ impl App<'_> {
                 }
             }
         }
+        self.sync_health();
+        self.sync_catalog();
+    }
+}
+
+impl App<'_> {
+    /// Opens a pool for `connection` in the background. The outcome comes
+    /// back as `ConnectionOpened` or `ConnectionFailed`. With `remember` the
+    /// password is stored once the connection works.
+    fn open_connection(&self, connection: Connection, remember: bool) {
+        let event_bus = self.event_bus.clone();
+        let force_read_only = self.force_read_only;
+        tokio::spawn(async move {
+            let event = match Session::open(connection.clone(), force_read_only).await {
+                Ok(session) => AppEvent::ConnectionOpened { session, remember },
+                Err(e) => AppEvent::ConnectionFailed {
+                    connection,
+                    error: format!("{:#}", e),
+                },
+            };
+            let _ = event_bus.send(event);
+        });
+    }
+
+    /// Runs `sql` on the active session. Writes against a production
+    /// connection first ask the user to type the connection name.
+    fn execute_sql(&mut self, sql: String, confirmed: bool) {
+        let Some(session) = self.active_session() else {
+            return;
+        };
+
+        let connection = &session.connection;
+        let is_write = is_write_statement(&sql);
+        if session.read_only && is_write {
+            let error = anyhow!("{} is open in read-only mode", connection.name);
+            self.show_error(" Read-only ", error);
+            return;
+        }
+        if connection.environment.is_production() && !confirmed && is_write {
+            let popup = InputPopup::new(
+                " Confirm Write on Production ",
+                "",
+                InputPurpose::ConfirmProductionWrite {
+                    sql,
+                    expected: connection.name.clone(),
+                },
+            )
+            .with_hint(format!(
+                "This statement may change data on {}. Type its name to run it.",
+                connection.name
+            ));
+            self.popup_stack.push(Popup::Input(Box::new(popup)));
+            return;
+        }
+
+        debug!("Executing statement on {}", connection.name);
+        let pool = session.pool.clone();
+        let event_bus = self.event_bus.clone();
+        tokio::spawn(async move {
+            let result = pool.execute(&sql).await.map_err(|e| format!("{:#}", e));
+            let _ = event_bus.send(AppEvent::SqlExecuted { result });
+        });
+    }
+
+    /// Opens a saved connection, filling in the password from the credential
+    /// store or asking for it first when needed.
+    fn connect_saved(&mut self, id: u32) {
+        let Some(mut connection) = self.connections.borrow().get(id).cloned() else {
+            return;
+        };
+
+        match &connection.password_source {
+            PasswordSource::Ask => {
+                let popup = InputPopup::masked(
+                    format!(" Password for {} ", connection.name),
+                    InputPurpose::ConnectionPassword { id },
+                );
+                self.popup_stack.push(Popup::Input(Box::new(popup)));
+            }
+            // the command runs in the background together with the connect
+            PasswordSource::Command { .. } => self.open_connection(connection, true),
+            source if source.is_external() => match import::resolve_password(&connection) {
+                Ok(Some(password)) => {
+                    connection.password = password;
+                    self.open_connection(connection, true);
+                }
+                Ok(None) => {
+                    let popup = InputPopup::masked(
+                        format!(" Password for {} ", connection.name),
+                        InputPurpose::ConnectionPassword { id },
+                    )
+                    .with_hint("No password found in the client config file");
+                    self.popup_stack.push(Popup::Input(Box::new(popup)));
+                }
+                Err(e) => self.show_error(" Password Lookup Failed ", e),
+            },
+            _ => {
+                if self.credentials.is_unlocked() {
+                    connection.password = self.stored_password(id);
+                } else if self.credentials.has_password(id) {
+                    self.pending_connect = Some(id);
+                    self.popup_stack
+                        .push(Popup::Input(Box::new(unlock_credentials_popup())));
+                    return;
+                }
+                self.open_connection(connection, true);
+            }
+        }
+    }
+
+    fn stored_password(&self, id: u32) -> String {
+        self.credentials
+            .get_password(id)
+            .unwrap_or_else(|e| {
+                error!("failed to read stored password: {:?}", e);
+                None
+            })
+            .unwrap_or_default()
+    }
+
+    /// Keeps the credential store in line with a connection that just worked.
+    fn remember_password(&mut self, connection: &Connection) {
+        // passwords from client config files and commands are fetched again next time
+        if connection.password_source.is_fetched() {
+            return;
+        }
+        if connection.password_source == PasswordSource::Ask || connection.password.is_empty() {
+            if let Err(e) = self.credentials.remove_password(connection.id) {
+                error!("failed to remove stored password: {:?}", e);
+            }
+            return;
+        }
+
+        if self.credentials.is_unlocked() {
+            if let Err(e) = self
+                .credentials
+                .set_password(connection.id, &connection.password)
+            {
+                error!("failed to store password: {:?}", e);
+            }
+            return;
+        }
+
+        self.pending_secret = Some((connection.id, connection.password.clone()));
+        let popup = if self.credentials.is_initialized() {
+            unlock_credentials_popup()
+        } else {
+            InputPopup::masked(" Master Passphrase ", InputPurpose::CreateCredentials)
+                .with_hint("Set a passphrase to store passwords encrypted (Esc: don't store)")
+        };
+        self.popup_stack.push(Popup::Input(Box::new(popup)));
+    }
+
+    /// Runs whatever was waiting for the credential store.
+    fn on_credentials_ready(&mut self) {
+        if let Some((id, password)) = self.pending_secret.take() {
+            if let Err(e) = self.credentials.set_password(id, &password) {
+                self.show_error(" Storing Password Failed ", e);
+            }
+        }
+        if let Some(id) = self.pending_connect.take() {
+            self.connect_saved(id);
+        }
+    }
+
+    fn show_error(&mut self, title: &str, error: anyhow::Error) {
+        error!("{}: {:?}", title.trim(), error);
+        self.popup_stack.push(Popup::Message(MessagePopup::error(
+            title,
+            format!("{:#}", error),
+        )));
+    }
+
+    /// Tries `connection` in the background without keeping the pool around.
+    fn test_connection(&self, connection: Connection, generation: u64) {
+        let event_bus = self.event_bus.clone();
+        tokio::spawn(async move {
+            let result = test_connection(&connection, CONNECTION_TEST_TIMEOUT)
+                .await
+                .map_err(|e| format!("{:#}", e));
+            let _ = event_bus.send(AppEvent::ConnectionTested { generation, result });
+        });
+    }
+
+    /// Adds `connection` to the saved ones, setting its id, or updates the
+    /// saved entry it was edited from.
+    fn save_connection(&mut self, connection: &mut Connection) -> anyhow::Result<()> {
+        let mut store = self.connections.borrow_mut();
+        if connection.id == 0 {
+            connection.id = store.add(connection.clone())?;
+            Ok(())
+        } else {
+            store.update(connection.clone())
+        }
+    }
+
+    fn on_connection_opened(&mut self, mut session: Session, remember: bool) {
+        // the connection popup (if any) is done once the connection works
+        if let Some(Popup::Connection(_)) = self.popup_stack.last() {
+            self.popup_stack.pop();
+        }
+
+        if remember {
+            self.remember_password(&session.connection);
+        }
+        let message = format!("Connected to {}", session.connection.name);
+
+        self.save_tab_state();
+        session.tab_index = DATABASES_TAB_INDEX;
+        // connecting again to an open connection replaces its session
+        match self.connection_session_index(session.connection.id) {
+            Some(index) => {
+                session.id = self.sessions[index].id;
+                let old_session = std::mem::replace(&mut self.sessions[index], session);
+                tokio::spawn(old_session.close());
+                self.active_session = index;
+            }
+            None => {
+                session.id = self.next_session_id;
+                self.next_session_id += 1;
+                self.sessions.push(session);
+                self.active_session = self.sessions.len() - 1;
+            }
+        }
+
+        let session = &self.sessions[self.active_session];
+        // a reopened session may point at another database
+        *self.catalog.borrow_mut().session_mut(session.id) = SessionCatalog {
+            has_schemas: session.connection.db_type == DBTypes::POSTGRES,
+            ..SessionCatalog::default()
+        };
+        load_databases(session, &self.event_bus);
+        self.tabs[DATABASES_TAB_INDEX].set_disabled(false);
+        self.current_tab_index = DATABASES_TAB_INDEX;
+        self.popup_stack
+            .push(Popup::Message(MessagePopup::info(" Connected ", message)));
+    }
+
+    fn session_index(&self, id: u32) -> Option<usize> {
+        self.sessions.iter().position(|session| session.id == id)
+    }
+
+    /// The open session of saved connection `id`.
+    fn connection_session_index(&self, id: u32) -> Option<usize> {
+        // unsaved connections (id 0) never match each other
+        if id == 0 {
+            return None;
+        }
+        self.sessions
+            .iter()
+            .position(|session| session.connection.id == id)
+    }
+
+    /// Remembers the shown tab in the active session before another one takes over.
+    fn save_tab_state(&mut self) {
+        let current_tab_index = self.current_tab_index;
+        if let Some(session) = self.sessions.get_mut(self.active_session) {
+            session.tab_index = current_tab_index;
+        }
+    }
+
+    fn discover_imports(&mut self) {
+        let candidates = import::discover();
+        debug!("Found {} connections to import", candidates.len());
+        if candidates.is_empty() {
+            self.popup_stack.push(Popup::Message(MessagePopup::info(
+                " Import Connections ",
+                "No connections found in ~/.pgpass, ~/.pg_service.conf or ~/.my.cnf.",
+            )));
+            return;
+        }
+
+        let existing = {
+            let store = self.connections.borrow();
+            candidates
+                .iter()
+                .map(|candidate| {
+                    store
+                        .connections()
+                        .iter()
+                        .any(|saved| saved.same_target(&candidate.connection))
+                })
+                .collect()
+        };
+        self.popup_stack
+            .push(Popup::Import(ImportPopup::new(candidates, existing)));
+    }
+
+    fn open_session_switcher(&mut self) {
+        if self.sessions.is_empty() {
+            return;
+        }
+
+        let entries = self
+            .sessions
+            .iter()
+            .enumerate()
+            .map(|(index, session)| {
+                let connection = &session.connection;
+                let mut badge = vec![];
+                if connection.environment != Environment::None {
+                    badge.push(format!(
+                        "[{}]",
+                        connection.environment.label().to_uppercase()
+                    ));
+                }
+                if session.read_only {
+                    badge.push("[RO]".to_string());
+                }
+                SessionEntry {
+                    id: session.id,
+                    label: format!(
+                        "{} [{}] {}",
+                        connection.name,
+                        connection.db_type.as_str(),
+                        connection.summary()
+                    ),
+                    badge: badge.join(" "),
+                    badge_style: self.theme.environment(&connection.environment),
+                    active: index == self.active_session,
+                }
+            })
+            .collect();
+        let popup = SessionSwitcherPopup::new(entries);
+        self.popup_stack
+            .push(Popup::SessionSwitcher(Box::new(popup)));
+    }
+
+    fn switch_session(&mut self, id: u32) {
+        let Some(index) = self.session_index(id) else {
+            return;
+        };
+
+        self.save_tab_state();
+        self.active_session = index;
+        self.current_tab_index = self.sessions[index].tab_index;
+        info!("Switched to {}", self.sessions[index].connection.name);
+    }
+
+    fn close_session(&mut self, id: u32) {
+        let Some(index) = self.session_index(id) else {
+            return;
+        };
+
+        self.save_tab_state();
+        let session = self.sessions.remove(index);
+        info!("Closing connection to {}", session.connection.name);
+        tokio::spawn(session.close());
+
+        if self.sessions.is_empty() {
+            self.active_session = 0;
+            self.tabs[DATABASES_TAB_INDEX].set_disabled(true);
+            self.current_tab_index = CONNECTIONS_TAB_INDEX;
+            return;
+        }
+        if index < self.active_session || self.active_session >= self.sessions.len() {
+            self.active_session = self.active_session.saturating_sub(1);
+        }
+        self.current_tab_index = self.sessions[self.active_session].tab_index;
+    }
+
+    /// Starts the health checks and reconnects that are due.
+    fn check_health(&mut self) {
+        let now = Instant::now();
+        for session in &mut self.sessions {
+            let id = session.id;
+            match session.health.poll(now) {
+                Some(HealthAction::Ping) => {
+                    let pool = session.pool.clone();
+                    let event_bus = self.event_bus.clone();
+                    tokio::spawn(async move {
+                        let result = match tokio::time::timeout(health::CHECK_TIMEOUT, pool.ping())
+                            .await
+                        {
+                            Ok(result) => result.map_err(|e| format!("{:#}", e)),
+                            Err(_) => Err("no answer within the health check timeout".to_string()),
+                        };
+                        let _ = event_bus.send(AppEvent::HealthChecked { id, result });
+                    });
+                }
+                Some(HealthAction::Reconnect) => {
+                    info!("Reconnecting to {}", session.connection.name);
+                    spawn_reconnect(session, self.force_read_only, &self.event_bus);
+                }
+                None => {}
+            }
+        }
+    }
+
+    /// Reopens the session of saved connection `id`, or opens it if it is not open.
+    fn reconnect(&mut self, id: u32) {
+        let Some(index) = self.connection_session_index(id) else {
+            self.connect_saved(id);
+            return;
+        };
+
+        let session = &mut self.sessions[index];
+        if session.health.start_manual_reconnect() {
+            info!("Reconnecting to {}", session.connection.name);
+            spawn_reconnect(session, self.force_read_only, &self.event_bus);
+        }
+    }
+
+    /// Swaps in the reopened session, keeping the tab it was on.
+    fn on_reconnected(&mut self, id: u32, result: Result<Session, String>) {
+        let Some(index) = self.session_index(id) else {
+            // closed while reconnecting
+            if let Ok(session) = result {
+                tokio::spawn(session.close());
+            }
+            return;
+        };
+
+        let now = Instant::now();
+        match result {
+            Ok(mut session) => {
+                info!("Reconnected to {}", session.connection.name);
+                session.id = id;
+                session.tab_index = self.sessions[index].tab_index;
+                let old_session = std::mem::replace(&mut self.sessions[index], session);
+                tokio::spawn(old_session.close());
+            }
+            Err(error) => {
+                let session = &mut self.sessions[index];
+                warn!(
+                    "Reconnecting to {} failed: {}",
+                    session.connection.name, error
+                );
+                session.health.record_reconnect_failure(error, now);
+            }
+        }
+    }
+
+    fn select_database(&mut self, name: String) {
+        let Some(session) = self.active_session() else {
+            return;
+        };
+        debug!("Showing {} on {}", name, session.connection.name);
+        let id = session.id;
+        let has_schemas = {
+            let mut catalog = self.catalog.borrow_mut();
+            let session_catalog = catalog.session_mut(id);
+            session_catalog.selected_database = Some(name);
+            session_catalog.selected_schema = None;
+            session_catalog.tables = Loadable::Loading;
+            session_catalog.open_table = None;
+            session_catalog.has_schemas
+        };
+        if has_schemas {
+            self.load_schemas();
+            self.tabs[SCHEMAS_TAB_INDEX].set_disabled(false);
+            self.current_tab_index = SCHEMAS_TAB_INDEX;
+        } else {
+            self.load_tables();
+            self.tabs[TABLES_TAB_INDEX].set_disabled(false);
+            self.current_tab_index = TABLES_TAB_INDEX;
+        }
+    }
+
+    /// Lists the schemas of the selected database in the background; the
+    /// result comes back as `SchemasLoaded`.
+    fn load_schemas(&mut self) {
+        let Some(session) = self.active_session() else {
+            return;
+        };
+        let mut catalog = self.catalog.borrow_mut();
+        let session_catalog = catalog.session_mut(session.id);
+        let Some(database) = session_catalog.selected_database.clone() else {
+            return;
+        };
+        session_catalog.schemas = Loadable::Loading;
+
+        let id = session.id;
+        load_from_database(
+            &session.databases,
+            database.clone(),
+            &self.event_bus,
+            |pool, _| async move { catalog::list_schemas(&pool).await },
+            move |result| AppEvent::SchemasLoaded {
+                session: id,
+                database,
+                result,
+            },
+        );
+    }
+
+    fn select_schema(&mut self, name: String) {
+        let Some(session) = self.active_session() else {
+            return;
+        };
+        debug!("Showing tables of schema {}", name);
+        let id = session.id;
+        self.catalog.borrow_mut().session_mut(id).selected_schema = Some(name);
+        self.load_tables();
+        self.tabs[TABLES_TAB_INDEX].set_disabled(false);
+        self.current_tab_index = TABLES_TAB_INDEX;
+    }
+
+    /// Lists the tables of the selected database in the background; the
+    /// result comes back as `TablesLoaded`.
+    fn load_tables(&mut self) {
+        let Some(session) = self.active_session() else {
+            return;
+        };
+        let mut catalog = self.catalog.borrow_mut();
+        let session_catalog = catalog.session_mut(session.id);
+        let Some(database) = session_catalog.selected_database.clone() else {
+            return;
+        };
+        let schema = session_catalog.selected_schema.clone();
+        session_catalog.tables = Loadable::Loading;
+        session_catalog.open_table = None;
+
+        let id = session.id;
+        let listed_schema = schema.clone();
+        load_from_database(
+            &session.databases,
+            database.clone(),
+            &self.event_bus,
+            |pool, database| async move {
+                catalog::list_tables(&pool, &database, listed_schema.as_deref()).await
+            },
+            move |result| AppEvent::TablesLoaded {
+                session: id,
+                database,
+                schema,
+                result,
+            },
+        );
     }
+
+    /// Reads the first rows, the columns and the indexes and keys of `table`
+    /// in the background; they come back as `TableDataLoaded`,
+    /// `TableColumnsLoaded` and `TableKeysLoaded`.
+    fn open_table(&mut self, table: TableInfo) {
+        let Some(session) = self.active_session() else {
+            return;
+        };
+        let mut catalog = self.catalog.borrow_mut();
+        let session_catalog = catalog.session_mut(session.id);
+        let Some(database) = session_catalog.selected_database.clone() else {
+            return;
+        };
+        debug!("Reading the rows of {}.{}", database, table.name);
+        session_catalog.open_table = Some(OpenTable {
+            table: table.clone(),
+            data: Loadable::Loading,
+            columns: Loadable::Loading,
+            keys: Loadable::Loading,
+        });
+
+        let id = session.id;
+        let (loaded, event_table) = (table.clone(), table.clone());
+        load_from_database(
+            &session.databases,
+            database.clone(),
+            &self.event_bus,
+            move |pool, database| async move { catalog::table_data(&pool, &database, &loaded).await },
+            move |result| AppEvent::TableDataLoaded {
+                session: id,
+                table: event_table,
+                result,
+            },
+        );
+        let (loaded, event_table) = (table.clone(), table.clone());
+        load_from_database(
+            &session.databases,
+            database.clone(),
+            &self.event_bus,
+            move |pool, database| async move { catalog::table_columns(&pool, &database, &loaded).await },
+            move |result| AppEvent::TableColumnsLoaded {
+                session: id,
+                table: event_table,
+                result,
+            },
+        );
+        let loaded = table.clone();
+        load_from_database(
+            &session.databases,
+            database,
+            &self.event_bus,
+            move |pool, database| async move { table_keys::table_keys(&pool, &database, &loaded).await },
+            move |result| AppEvent::TableKeysLoaded {
+                session: id,
+                table,
+                result,
+            },
+        );
+    }
+
+    /// Points the Databases, Schemas and Tables tabs at the active session and
+    /// forgets the catalogs of closed sessions.
+    fn sync_catalog(&mut self) {
+        let open: Vec<u32> = self.sessions.iter().map(|session| session.id).collect();
+        let mut catalog = self.catalog.borrow_mut();
+        catalog.retain_sessions(&open);
+        catalog.active_session = self.active_session().map(|session| session.id);
+        let (has_schemas, has_tables) = catalog.active().map_or((false, false), |session| {
+            let has_database = session.selected_database.is_some();
+            (
+                session.has_schemas && has_database,
+                has_database && (!session.has_schemas || session.selected_schema.is_some()),
+            )
+        });
+        self.tabs[SCHEMAS_TAB_INDEX].set_disabled(!has_schemas);
+        self.tabs[TABLES_TAB_INDEX].set_disabled(!has_tables);
+        if self.current_tab_index >= SCHEMAS_TAB_INDEX
+            && self.tabs[self.current_tab_index].is_disabled()
+        {
+            self.current_tab_index = if has_schemas {
+                SCHEMAS_TAB_INDEX
+            } else if self.sessions.is_empty() {
+                CONNECTIONS_TAB_INDEX
+            } else {
+                DATABASES_TAB_INDEX
+            };
+        }
+    }
+
+    /// Publishes the health of the open sessions to the connections tab.
+    fn sync_health(&self) {
+        let mut health = self.health.borrow_mut();
+        health.clear();
+        health.extend(
+            self.sessions
+                .iter()
+                .filter(|session| session.connection.id != 0)
+                .map(|session| (session.connection.id, session.health.status)),
+        );
+    }
+}
+
+/// Opens a new session for the connection of `session` in the background;
+/// the result comes back as `Reconnected`.
+fn spawn_reconnect(session: &Session, force_read_only: bool, event_bus: &mpsc::Sender<AppEvent>) {
+    let id = session.id;
+    let connection = session.connection.clone();
+    let event_bus = event_bus.clone();
+    tokio::spawn(async move {
+        let result = Session::open(connection, force_read_only)
+            .await
+            .map_err(|e| format!("{:#}", e));
+        let _ = event_bus.send(AppEvent::Reconnected { id, result });
+    });
+}
+
+/// Lists the databases of `session` in the background; the result comes back
+/// as `DatabasesLoaded`.
+fn load_databases(session: &Session, event_bus: &mpsc::Sender<AppEvent>) {
+    let id = session.id;
+    let pool = session.pool.clone();
+    let event_bus = event_bus.clone();
+    tokio::spawn(async move {
+        let result = catalog::list_databases(&pool)
+            .await
+            .map_err(|e| format!("{:#}", e));
+        let _ = event_bus.send(AppEvent::DatabasesLoaded {
+            session: id,
+            result,
+        });
+    });
+}
+
+/// Runs `load` on the pool of `database` in the background and sends its
+/// result back as the event made by `loaded`.
+fn load_from_database<T, F>(
+    pools: &DatabasePools,
+    database: String,
+    event_bus: &mpsc::Sender<AppEvent>,
+    load: impl FnOnce(DbPool, String) -> F + Send + 'static,
+    loaded: impl FnOnce(Result<T, String>) -> AppEvent + Send + 'static,
+) where
+    F: Future<Output = anyhow::Result<T>> + Send,
+    T: Send + 'static,
+{
+    let pools = pools.clone();
+    let event_bus = event_bus.clone();
+    tokio::spawn(async move {
+        let result = match pools.get(&database).await {
+            Ok(pool) => load(pool, database).await,
+            Err(e) => Err(e),
+        }
+        .map_err(|e| format!("{:#}", e));
+        let _ = event_bus.send(loaded(result));
+    });
+}
+
+fn unlock_credentials_popup<'a>() -> InputPopup<'a> {
+    InputPopup::masked(" Unlock Credentials ", InputPurpose::UnlockCredentials)
+        .with_hint("Enter the master passphrase (Esc: continue without stored passwords)")
 }
 
 #[tokio::main]
 async fn main() -> io::Result<()> {
+    let mut cli_args = args::process_cmdline().map_err(|e| io::Error::other(format!("{:#}", e)))?;
+
     let _ = init_logger(LevelFilter::Debug);
     set_default_level(LevelFilter::Debug);
 
     let (tx, rx) = mpsc::channel();
-    let mut app = App::new(tx);
+    let start = cli_args.start.take();
+    let mut app = App::new(tx, cli_args);
+    if let Some(start) = start {
+        app.connect_on_start(start)
+            .map_err(|e| io::Error::other(format!("{:#}", e)))?;
+    }
     debug!("Starting application");
 
     let mut terminal = tui::init()?;
@@ -371,3 +1737,118 @@ async fn main() -> io::Result<()> {
     tui::restore()?;
     app_result
 }
+
+#[cfg(test)]
+mod tests {
+    use std::{env, sync::Mutex};
+
+    use super::*;
+    use db::{connection::PasswordSource, pool::PoolConfig};
+    use tempfile::TempDir;
+
+    const SECRET: &str = "hunter2";
+
+    static RECORDS: Mutex<Vec<String>> = Mutex::new(vec![]);
+
+    /// Keeps every log message, from any test, to check what reaches the log.
+    struct CaptureLogger;
+
+    impl Log for CaptureLogger {
+        fn enabled(&self, _metadata: &Metadata) -> bool {
+            true
+        }
+
+        fn log(&self, record: &Record) {
+            RECORDS.lock().unwrap().push(record.args().to_string());
+        }
+
+        fn flush(&self) {}
+    }
+
+    static LOGGER: CaptureLogger = CaptureLogger;
+
+    fn logged(text: &str) -> bool {
+        RECORDS
+            .lock()
+            .unwrap()
+            .iter()
+            .any(|line| line.contains(text))
+    }
+
+    #[tokio::test]
+    async fn test_connection_path_logs_no_secrets() {
+        log::set_logger(&LOGGER).unwrap();
+        log::set_max_level(LevelFilter::Trace);
+        let config = TempDir::new().unwrap();
+        env::set_var("XDG_CONFIG_HOME", config.path());
+
+        let (tx, rx) = mpsc::channel();
+        let mut app = App::new(
+            tx.clone(),
+            CliArgs {
+                theme: config.path().join("theme.ron"),
+                read_only: false,
+                start: None,
+            },
+        );
+        // nothing listens on port 1, connecting fails right away
+        let connection = Connection {
+            name: "orders".to_string(),
+            db_type: DBTypes::POSTGRES,
+            host: "127.0.0.1".to_string(),
+            port: 1,
+            user: "app".to_string(),
+            password: SECRET.to_string(),
+            params: [("sslpassword".to_string(), SECRET.to_string())].into(),
+            pool: PoolConfig {
+                connect_timeout_secs: 1,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let with_command = Connection {
+            name: "billing".to_string(),
+            password: String::new(),
+            password_source: PasswordSource::Command {
+                command: format!("PGPASSWORD={} false -token={}", SECRET, SECRET),
+            },
+            ..connection.clone()
+        };
+
+        app.popup_stack
+            .push(Popup::Connection(Box::new(DbConnectionPopup::edit(
+                &connection,
+            ))));
+        for event in [
+            AppEvent::TestConnection {
+                connection: connection.clone(),
+                generation: 1,
+            },
+            AppEvent::ConnectionDetailsSubmitted {
+                connection: connection.clone(),
+            },
+            AppEvent::CancelClosePopup,
+            AppEvent::ConnectionDetailsSubmitted {
+                connection: with_command,
+            },
+        ] {
+            tx.send(event).unwrap();
+        }
+
+        let deadline = Instant::now() + Duration::from_secs(20);
+        while !(logged("Connection test finished")
+            && logged("Failed to connect to orders")
+            && logged("Failed to connect to billing"))
+        {
+            assert!(Instant::now() < deadline, "{:#?}", RECORDS.lock().unwrap());
+            app.check_event_loop(&rx);
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+
+        assert!(logged("Connection Details Submitted"));
+        assert!(logged("password command 'false ***'"));
+        for line in RECORDS.lock().unwrap().iter() {
+            assert!(!line.contains(SECRET), "{}", line);
+        }
+    }
+}
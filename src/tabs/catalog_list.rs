@@ -0,0 +1,191 @@
+use std::sync::mpsc;
+
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{prelude::*, style::Color, widgets::*};
+
+use crate::{
+    db::catalog::{Loadable, SessionCatalog, SharedCatalog},
+    AppEvent,
+};
+
+/// An entry of a [`CatalogList`], e.g. a database or a schema.
+pub trait CatalogEntry: Clone {
+    /// Headers and widths of the columns after the name.
+    const COLUMNS: &'static [(&'static str, Constraint)];
+
+    fn name(&self) -> &str;
+    /// Marked with `*` and shown in bold.
+    fn is_current(&self) -> bool;
+    /// One cell for each of [`Self::COLUMNS`].
+    fn cells(&self) -> Vec<Cell<'static>>;
+}
+
+/// One level of the catalog of the active session as a table to pick an entry
+/// from, shared by the Databases and Schemas tabs.
+#[derive(Debug)]
+pub struct CatalogList<T> {
+    pub catalog: SharedCatalog,
+    pub table_state: TableState,
+    /// What is listed, e.g. `databases`, for the loading and error messages.
+    noun: &'static str,
+    entries: fn(&SessionCatalog) -> &Loadable<Vec<T>>,
+    /// Sent with the name of the entry picked with Enter.
+    select_event: fn(String) -> AppEvent,
+    /// Sent on `r`.
+    refresh_event: fn() -> AppEvent,
+}
+
+impl<T: CatalogEntry> CatalogList<T> {
+    pub fn new(
+        catalog: SharedCatalog,
+        noun: &'static str,
+        entries: fn(&SessionCatalog) -> &Loadable<Vec<T>>,
+        select_event: fn(String) -> AppEvent,
+        refresh_event: fn() -> AppEvent,
+    ) -> Self {
+        Self {
+            catalog,
+            table_state: TableState::default().with_selected(Some(0)),
+            noun,
+            entries,
+            select_event,
+            refresh_event,
+        }
+    }
+
+    fn loaded(&self) -> Vec<T> {
+        match self.catalog.borrow().active().map(self.entries) {
+            Some(Loadable::Loaded(entries)) => entries.clone(),
+            _ => vec![],
+        }
+    }
+
+    fn select(&mut self, step: isize) {
+        let len = self.loaded().len() as isize;
+        if len == 0 {
+            return;
+        }
+        let current = self.table_state.selected().unwrap_or(0) as isize;
+        self.table_state
+            .select(Some((current + step).rem_euclid(len) as usize));
+    }
+
+    pub fn draw(&mut self, frame: &mut Frame, area: Rect) {
+        let entries = match self.catalog.borrow().active().map(self.entries) {
+            Some(Loadable::Loaded(entries)) => entries.clone(),
+            Some(Loadable::Failed(error)) => {
+                frame.render_widget(
+                    Paragraph::new(format!("Failed to list the {}: {}", self.noun, error))
+                        .style(Style::default().fg(Color::Red))
+                        .wrap(Wrap { trim: false }),
+                    area,
+                );
+                return;
+            }
+            Some(Loadable::Loading) | None => {
+                frame.render_widget(Paragraph::new(format!("Loading {}...", self.noun)), area);
+                return;
+            }
+        };
+
+        // the list may have shrunk after a refresh
+        if self.table_state.selected().unwrap_or(0) >= entries.len() {
+            self.table_state
+                .select(Some(entries.len().saturating_sub(1)));
+        }
+
+        let rows = entries.iter().map(|entry| {
+            let name_style = if entry.is_current() {
+                Style::default()
+                    .fg(Color::White)
+                    .add_modifier(Modifier::BOLD)
+            } else {
+                Style::default().fg(Color::White)
+            };
+            let mut cells = vec![
+                Cell::from(if entry.is_current() { "*" } else { "" }),
+                Cell::from(Span::styled(entry.name().to_string(), name_style)),
+            ];
+            cells.extend(entry.cells());
+            Row::new(cells)
+        });
+        let header = Row::new(
+            ["", "Name"]
+                .into_iter()
+                .chain(T::COLUMNS.iter().map(|(title, _)| *title)),
+        )
+        .style(Style::default().fg(Color::DarkGray));
+        let widths = [Constraint::Length(1), Constraint::Min(20)]
+            .into_iter()
+            .chain(T::COLUMNS.iter().map(|(_, width)| *width));
+        let table = Table::new(rows, widths)
+            .header(header)
+            .highlight_style(Color::Yellow)
+            .highlight_spacing(HighlightSpacing::Always)
+            .highlight_symbol(">> ");
+
+        frame.render_stateful_widget(table, area, &mut self.table_state);
+    }
+
+    pub fn handle_input(&mut self, key: KeyEvent, app_event_bus: &mpsc::Sender<AppEvent>) {
+        match key.code {
+            KeyCode::Enter => {
+                let selected = self.table_state.selected().unwrap_or(0);
+                if let Some(entry) = self.loaded().into_iter().nth(selected) {
+                    app_event_bus
+                        .send((self.select_event)(entry.name().to_string()))
+                        .unwrap();
+                }
+            }
+            KeyCode::Char('r') => app_event_bus.send((self.refresh_event)()).unwrap(),
+            KeyCode::Down | KeyCode::Char('j') => self.select(1),
+            KeyCode::Up | KeyCode::Char('k') => self.select(-1),
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::catalog::DatabaseInfo;
+    use crossterm::event::KeyModifiers;
+
+    #[test]
+    fn test_enter_selects_entry() {
+        let catalog = SharedCatalog::default();
+        {
+            let mut catalog = catalog.borrow_mut();
+            catalog.active_session = Some(1);
+            catalog.session_mut(1).databases = Loadable::Loaded(
+                ["app", "postgres"]
+                    .into_iter()
+                    .map(|name| DatabaseInfo {
+                        name: name.to_string(),
+                        size: None,
+                        owner: None,
+                        current: false,
+                    })
+                    .collect(),
+            );
+        }
+        let mut list = CatalogList::new(
+            catalog,
+            "databases",
+            |catalog| &catalog.databases,
+            |name| AppEvent::SelectDatabase { name },
+            || AppEvent::LoadDatabases,
+        );
+        let (tx, rx) = mpsc::channel();
+        let key = |code| KeyEvent::new(code, KeyModifiers::NONE);
+
+        list.handle_input(key(KeyCode::Up), &tx);
+        list.handle_input(key(KeyCode::Enter), &tx);
+        assert!(matches!(
+            rx.try_recv(),
+            Ok(AppEvent::SelectDatabase { name }) if name == "postgres"
+        ));
+        list.handle_input(key(KeyCode::Char('r')), &tx);
+        assert!(matches!(rx.try_recv(), Ok(AppEvent::LoadDatabases)));
+    }
+}
@@ -1,61 +1,383 @@
 use std::{
-    io::{self, Result},
+    collections::{BTreeMap, BTreeSet},
+    io,
     sync::mpsc,
 };
 
-use crossterm::event::{KeyCode, KeyEvent};
-use ratatui::{
-    prelude::*,
-    style::Color,
-    widgets::{block::*, *},
-};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::{prelude::*, style::Color, widgets::*};
+use tui_textarea::TextArea;
 
-use crate::{App, AppEvent};
+use crate::{
+    db::{
+        connection::{Connection, Environment},
+        health::SharedHealth,
+        store::SharedConnections,
+    },
+    tabs::db_types_tab::DBTypes,
+    ui::{
+        fuzzy::{fuzzy_filter, highlighted},
+        style::SharedTheme,
+    },
+    AppEvent,
+};
 
 use super::DBTab;
 
+const CREATE_NEW_CONNECTION: &str = "Create New Connection";
+const KEY_HINTS: &str =
+    "Enter: connect, open/close folder | /: filter | R: reconnect | e: edit | d: delete | y: duplicate | r: rename | i: import";
+const FILTER_HINTS: &str = "Enter: connect | Esc: clear";
+
+/// One line of the connections list.
+#[derive(Debug, Clone, PartialEq)]
+enum Row {
+    CreateNew,
+    Folder {
+        name: String,
+        count: usize,
+        collapsed: bool,
+    },
+    Connection {
+        id: u32,
+        in_folder: bool,
+    },
+    /// A connection matching the filter, with the matched char positions of
+    /// its searchable text.
+    Match {
+        id: u32,
+        indices: Vec<usize>,
+    },
+}
+
 #[derive(Debug)]
 pub struct DbConnectionsTab {
     pub title: String,
-    pub connections: Vec<Connection>,
-    pub selected: usize,
+    pub connections: SharedConnections,
+    /// Health of the connections that are open.
+    pub health: SharedHealth,
+    pub theme: SharedTheme,
     pub list_state: ListState,
     pub num_connections: usize,
+    /// Folders whose connections are hidden.
+    collapsed: BTreeSet<String>,
+    /// Fuzzy filter opened with `/`, `None` when the full list is shown.
+    filter: Option<TextArea<'static>>,
+    /// Key presses go into the filter instead of acting on the list.
+    filter_focused: bool,
     pub disabled: bool,
 }
 
-#[derive(Debug)]
-pub struct Connection {
-    pub name: String,
-    pub host: String,
-    pub port: u16,
-    pub user: String,
-    pub password: String,
-    pub is_create_new: bool,
-}
-
-impl Default for DbConnectionsTab {
-    fn default() -> Self {
-        let create_new_connection = Connection {
-            name: "Create New Connection".to_string(),
-            host: "".to_string(),
-            user: "".to_string(),
-            password: "".to_string(),
-            port: 0,
-            is_create_new: true,
-        };
-
+impl DbConnectionsTab {
+    pub fn new(connections: SharedConnections, health: SharedHealth, theme: SharedTheme) -> Self {
         Self {
             title: "Connections".to_string(),
-            connections: vec![create_new_connection],
-            selected: 0,
+            connections,
+            health,
+            theme,
             list_state: ListState::default().with_selected(Some(0)),
             num_connections: 1,
+            collapsed: BTreeSet::new(),
+            filter: None,
+            filter_focused: false,
             disabled: false,
         }
     }
 }
 
+impl DbConnectionsTab {
+    /// Connections without a folder come first, in saved order, followed by
+    /// the folders sorted by name. While filtering, only the matching
+    /// connections are listed, best match first and without folders.
+    fn rows(&self) -> Vec<Row> {
+        let pattern = self.filter_pattern();
+        if !pattern.trim().is_empty() {
+            return self.matching_rows(pattern);
+        }
+
+        let store = self.connections.borrow();
+        let mut rows = vec![Row::CreateNew];
+        let mut folders: BTreeMap<&str, Vec<u32>> = BTreeMap::new();
+        for conn in store.connections() {
+            if conn.folder.is_empty() {
+                rows.push(Row::Connection {
+                    id: conn.id,
+                    in_folder: false,
+                });
+            } else {
+                folders.entry(&conn.folder).or_default().push(conn.id);
+            }
+        }
+
+        for (name, ids) in folders {
+            let collapsed = self.collapsed.contains(name);
+            rows.push(Row::Folder {
+                name: name.to_string(),
+                count: ids.len(),
+                collapsed,
+            });
+            if !collapsed {
+                rows.extend(ids.into_iter().map(|id| Row::Connection {
+                    id,
+                    in_folder: true,
+                }));
+            }
+        }
+        rows
+    }
+
+    fn filter_pattern(&self) -> &str {
+        self.filter
+            .as_ref()
+            .map_or("", |filter| filter.lines()[0].as_str())
+    }
+
+    /// The connections the filter matches, all of them while it is empty.
+    fn match_count(&self) -> usize {
+        let pattern = self.filter_pattern();
+        if pattern.trim().is_empty() {
+            self.connections.borrow().connections().len()
+        } else {
+            self.matching_rows(pattern).len()
+        }
+    }
+
+    fn matching_rows(&self, pattern: &str) -> Vec<Row> {
+        let store = self.connections.borrow();
+        let texts: Vec<(u32, String)> = store
+            .connections()
+            .iter()
+            .map(|conn| (conn.id, self.searchable_text(conn)))
+            .collect();
+        fuzzy_filter(pattern, texts.iter().map(|(id, text)| (*id, text.as_str())))
+            .into_iter()
+            .map(|(id, fuzzy_match)| Row::Match {
+                id,
+                indices: fuzzy_match.indices,
+            })
+            .collect()
+    }
+
+    /// The text of a connection row in its differently styled parts: tag,
+    /// name, driver and target, and the folder while filtering. The flag marks
+    /// the parts the filter matches against.
+    fn segments(&self, conn: &Connection) -> Vec<(String, Style, bool)> {
+        let mut segments = vec![];
+        if conn.environment != Environment::None {
+            let style = self.theme.environment(&conn.environment);
+            segments.push(("[".to_string(), style, false));
+            segments.push((conn.environment.label().to_string(), style, true));
+            segments.push(("] ".to_string(), style, false));
+        }
+        segments.push((conn.name.clone(), Style::default().fg(Color::White), true));
+
+        let target = Style::default().fg(Color::DarkGray);
+        segments.push((format!("  [{}] ", conn.db_type.as_str()), target, false));
+        if conn.db_type == DBTypes::SQLITE {
+            segments.push((conn.database.clone(), target, true));
+        } else {
+            if !conn.user.is_empty() {
+                segments.push((format!("{}@", conn.user), target, false));
+            }
+            if conn.socket.is_empty() {
+                segments.push((conn.host.clone(), target, true));
+                segments.push((format!(":{}", conn.port()), target, false));
+            } else {
+                segments.push(("unix:".to_string(), target, false));
+                segments.push((conn.socket.clone(), target, true));
+            }
+            if !conn.database.is_empty() {
+                segments.push(("/".to_string(), target, false));
+                segments.push((conn.database.clone(), target, true));
+            }
+        }
+
+        if self.filter.is_some() && !conn.folder.is_empty() {
+            segments.push((
+                format!("  {}/", conn.folder),
+                Style::default().fg(Color::Cyan),
+                false,
+            ));
+        }
+        segments
+    }
+
+    /// What the filter matches against: name, host, database and tag.
+    fn searchable_text(&self, conn: &Connection) -> String {
+        self.segments(conn)
+            .into_iter()
+            .filter(|(_, _, searchable)| *searchable)
+            .map(|(text, _, _)| text)
+            .collect()
+    }
+
+    fn open_filter(&mut self) {
+        if self.filter.is_none() {
+            let mut filter = TextArea::default();
+            filter.set_cursor_line_style(Style::default());
+            self.filter = Some(filter);
+            self.list_state.select(Some(0));
+        }
+        self.filter_focused = true;
+    }
+
+    /// Back to the full list, keeping the selected connection selected if its
+    /// folder is open.
+    fn close_filter(&mut self) {
+        let selected = self.selected_connection_id();
+        self.filter = None;
+        self.filter_focused = false;
+        let index = selected.and_then(|selected| {
+            self.rows()
+                .iter()
+                .position(|row| matches!(row, Row::Connection { id, .. } if *id == selected))
+        });
+        self.list_state.select(Some(index.unwrap_or(0)));
+    }
+
+    fn handle_filter_input(&mut self, key: KeyEvent, app_event_bus: &mpsc::Sender<AppEvent>) {
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        match key.code {
+            KeyCode::Esc => self.close_filter(),
+            KeyCode::Enter => {
+                self.filter_focused = false;
+                self.activate_selected(app_event_bus);
+            }
+            KeyCode::Down => self.select_next(),
+            KeyCode::Char('n') if ctrl => self.select_next(),
+            KeyCode::Up => self.select_previous(),
+            KeyCode::Char('p') if ctrl => self.select_previous(),
+            _ => {
+                let changed = self.filter.as_mut().is_some_and(|filter| filter.input(key));
+                if changed {
+                    // the best match comes first
+                    self.list_state.select(Some(0));
+                }
+            }
+        }
+    }
+
+    fn activate_selected(&mut self, app_event_bus: &mpsc::Sender<AppEvent>) {
+        match self.selected_row() {
+            Some(Row::Connection { id, .. } | Row::Match { id, .. }) => {
+                app_event_bus.send(AppEvent::Connect { id }).unwrap()
+            }
+            Some(Row::Folder { name, .. }) => self.toggle_folder(name),
+            Some(Row::CreateNew) => app_event_bus.send(AppEvent::NewConnection).unwrap(),
+            None => {}
+        }
+    }
+
+    fn select_next(&mut self) {
+        if self.num_connections == 0 {
+            return;
+        }
+        let current_selected = self.list_state.selected().unwrap_or(0);
+        self.list_state
+            .select(Some((current_selected + 1) % self.num_connections));
+    }
+
+    fn select_previous(&mut self) {
+        if self.num_connections == 0 {
+            return;
+        }
+        let current_selected = self.list_state.selected().unwrap_or(0);
+        if current_selected == 0 {
+            self.list_state.select(Some(self.num_connections - 1));
+        } else {
+            self.list_state.select(Some(current_selected - 1));
+        }
+    }
+
+    fn selected_row(&self) -> Option<Row> {
+        let selected = self.list_state.selected().unwrap_or(0);
+        self.rows().into_iter().nth(selected)
+    }
+
+    /// Id of the highlighted saved connection, `None` for the "create new" entry and folders.
+    fn selected_connection_id(&self) -> Option<u32> {
+        match self.selected_row() {
+            Some(Row::Connection { id, .. } | Row::Match { id, .. }) => Some(id),
+            _ => None,
+        }
+    }
+
+    fn toggle_folder(&mut self, name: String) {
+        if !self.collapsed.remove(&name) {
+            self.collapsed.insert(name);
+        }
+    }
+
+    /// `indices` are the chars of the searchable text matched by the filter.
+    fn connection_item(&self, id: u32, in_folder: bool, indices: &[usize]) -> ListItem<'static> {
+        let connections = self.connections.borrow();
+        let Some(conn) = connections.get(id) else {
+            return ListItem::new("");
+        };
+
+        let mut spans = vec![];
+        if in_folder {
+            spans.push(Span::raw("  "));
+        }
+        match self.health.borrow().get(&id) {
+            Some(status) => spans.push(Span::styled("\u{25CF} ", self.theme.health(*status))),
+            None => spans.push(Span::raw("  ")),
+        }
+        // `indices` point into the searchable parts only
+        let mut offset = 0;
+        for (text, style, searchable) in self.segments(conn) {
+            if !searchable {
+                spans.push(Span::styled(text, style));
+                continue;
+            }
+            let len = text.chars().count();
+            let local: Vec<usize> = indices
+                .iter()
+                .filter(|i| (offset..offset + len).contains(*i))
+                .map(|i| i - offset)
+                .collect();
+            spans.extend(highlighted(&text, &local, style));
+            offset += len;
+        }
+        ListItem::new(Line::from(spans))
+    }
+
+    fn draw_filter(&mut self, frame: &mut Frame, area: Rect) {
+        let count = self.match_count();
+        let focused = self.filter_focused;
+        let Some(filter) = self.filter.as_mut() else {
+            return;
+        };
+        let chunks = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([
+                Constraint::Length(2),
+                Constraint::Min(10),
+                Constraint::Length(FILTER_HINTS.len() as u16 + 16),
+            ])
+            .split(area);
+
+        frame.render_widget(
+            Paragraph::new(Span::styled("/", Style::default().fg(Color::Yellow))),
+            chunks[0],
+        );
+        filter.set_cursor_style(if focused {
+            Style::default().bg(Color::White).fg(Color::Black)
+        } else {
+            Style::default()
+        });
+        frame.render_widget(filter.widget(), chunks[1]);
+        frame.render_widget(
+            Paragraph::new(Span::styled(
+                format!("{} matches | {}", count, FILTER_HINTS),
+                Style::default().fg(Color::DarkGray),
+            ))
+            .alignment(Alignment::Right),
+            chunks[2],
+        );
+    }
+}
+
 impl DBTab for DbConnectionsTab {
     fn draw(&mut self, frame: &mut Frame, area: Rect) -> io::Result<()> {
         let chunks = Layout::default()
@@ -63,18 +385,53 @@ impl DBTab for DbConnectionsTab {
             .constraints([Constraint::Length(1), Constraint::Min(0)])
             .split(area);
 
-        let items = self
-            .connections
-            .iter()
-            .map(|conn| {
-                ListItem::new(Span::styled(
-                    conn.name.clone(),
+        let items: Vec<ListItem> = self
+            .rows()
+            .into_iter()
+            .map(|row| match row {
+                Row::CreateNew => ListItem::new(Span::styled(
+                    CREATE_NEW_CONNECTION,
                     Style::default().fg(Color::White),
-                ))
+                )),
+                Row::Folder {
+                    name,
+                    count,
+                    collapsed,
+                } => ListItem::new(Span::styled(
+                    format!(
+                        "{} {}/ ({})",
+                        if collapsed { "\u{25B8}" } else { "\u{25BE}" },
+                        name,
+                        count
+                    ),
+                    Style::default().fg(Color::Cyan),
+                )),
+                Row::Connection { id, in_folder } => self.connection_item(id, in_folder, &[]),
+                Row::Match { id, indices } => self.connection_item(id, false, &indices),
             })
-            .collect::<Vec<ListItem>>();
+            .collect();
 
         self.num_connections = items.len();
+        if self.num_connections == 0 {
+            self.list_state.select(None);
+        } else if self.list_state.selected().unwrap_or(0) >= self.num_connections {
+            // the selected entry may have been deleted
+            self.list_state.select(Some(self.num_connections - 1));
+        } else if self.list_state.selected().is_none() {
+            self.list_state.select(Some(0));
+        }
+
+        if self.filter.is_some() {
+            self.draw_filter(frame, chunks[0]);
+        } else {
+            frame.render_widget(
+                Paragraph::new(Span::styled(
+                    KEY_HINTS,
+                    Style::default().fg(Color::DarkGray),
+                )),
+                chunks[0],
+            );
+        }
 
         let items = List::new(items)
             .block(Block::default().borders(Borders::NONE))
@@ -92,29 +449,49 @@ impl DBTab for DbConnectionsTab {
         key: KeyEvent,
         app_event_bus: &mpsc::Sender<AppEvent>,
     ) -> io::Result<()> {
+        if self.filter_focused {
+            self.handle_filter_input(key, app_event_bus);
+            return Ok(());
+        }
+
         match key.code {
-            KeyCode::Enter => {
-                app_event_bus.send(AppEvent::NewConnection).unwrap();
+            KeyCode::Enter => self.activate_selected(app_event_bus),
+            KeyCode::Char('/') => self.open_filter(),
+            KeyCode::Esc if self.filter.is_some() => self.close_filter(),
+            KeyCode::Char('i') => app_event_bus.send(AppEvent::DiscoverImports).unwrap(),
+            KeyCode::Char('R') => {
+                if let Some(id) = self.selected_connection_id() {
+                    app_event_bus.send(AppEvent::Reconnect { id }).unwrap();
+                }
             }
-            KeyCode::Down | KeyCode::Char('j') => {
-                let current_selected = self.list_state.selected().unwrap_or(0);
-
-                if current_selected == self.num_connections - 1 {
-                    self.list_state.select(Some(0));
-                } else {
-                    self.list_state
-                        .select(Some((current_selected + 1).min(self.num_connections - 1)));
+            KeyCode::Char('e') => {
+                if let Some(id) = self.selected_connection_id() {
+                    app_event_bus.send(AppEvent::EditConnection { id }).unwrap();
                 }
             }
-            KeyCode::Up | KeyCode::Char('k') => {
-                let current_selected = self.list_state.selected().unwrap_or(0);
-
-                if current_selected == 0 {
-                    self.list_state.select(Some(self.num_connections - 1));
-                } else {
-                    self.list_state.select(Some(current_selected - 1));
+            KeyCode::Char('d') => {
+                if let Some(id) = self.selected_connection_id() {
+                    app_event_bus
+                        .send(AppEvent::ConfirmDeleteConnection { id })
+                        .unwrap();
                 }
             }
+            KeyCode::Char('y') => {
+                if let Some(id) = self.selected_connection_id() {
+                    app_event_bus
+                        .send(AppEvent::DuplicateConnection { id })
+                        .unwrap();
+                }
+            }
+            KeyCode::Char('r') => {
+                if let Some(id) = self.selected_connection_id() {
+                    app_event_bus
+                        .send(AppEvent::StartRenameConnection { id })
+                        .unwrap();
+                }
+            }
+            KeyCode::Down | KeyCode::Char('j') => self.select_next(),
+            KeyCode::Up | KeyCode::Char('k') => self.select_previous(),
             _ => {}
         }
         Ok(())
@@ -131,4 +508,158 @@ impl DBTab for DbConnectionsTab {
     fn get_title(&self) -> String {
         self.title.clone()
     }
+
+    fn handles_key(&self, key: &KeyEvent) -> bool {
+        self.filter_focused || (self.filter.is_some() && key.code == KeyCode::Esc)
+    }
+
+    fn handle_paste(&mut self, text: &str) {
+        if !self.filter_focused {
+            return;
+        }
+        if let Some(filter) = self.filter.as_mut() {
+            let text: String = text.chars().filter(|c| *c != '\n' && *c != '\r').collect();
+            filter.insert_str(text);
+            self.list_state.select(Some(0));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, rc::Rc};
+
+    use super::*;
+    use crate::db::{connection::Connection, store::ConnectionStore};
+    use pretty_assertions::assert_eq;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_rows_grouped_by_folder() {
+        let dir = TempDir::new().unwrap();
+        let mut store = ConnectionStore::load(dir.path().join("connections.ron")).unwrap();
+        for (name, folder) in [("b1", "b"), ("top", ""), ("a1", "a"), ("b2", "b")] {
+            let connection = Connection {
+                name: name.to_string(),
+                folder: folder.to_string(),
+                ..Default::default()
+            };
+            store.add(connection).unwrap();
+        }
+
+        let mut tab = DbConnectionsTab::new(
+            Rc::new(RefCell::new(store)),
+            SharedHealth::default(),
+            SharedTheme::default(),
+        );
+        let folder = |name: &str, count| Row::Folder {
+            name: name.to_string(),
+            count,
+            collapsed: false,
+        };
+        let conn = |id| Row::Connection {
+            id,
+            in_folder: true,
+        };
+        assert_eq!(
+            tab.rows(),
+            vec![
+                Row::CreateNew,
+                Row::Connection {
+                    id: 2,
+                    in_folder: false
+                },
+                folder("a", 1),
+                conn(3),
+                folder("b", 2),
+                conn(1),
+                conn(4),
+            ]
+        );
+
+        tab.toggle_folder("a".to_string());
+        assert_eq!(
+            tab.rows()[2],
+            Row::Folder {
+                name: "a".to_string(),
+                count: 1,
+                collapsed: true
+            }
+        );
+        assert_eq!(tab.rows().len(), 6);
+    }
+
+    #[test]
+    fn test_filter_narrows_and_keeps_selection() {
+        let dir = TempDir::new().unwrap();
+        let mut store = ConnectionStore::load(dir.path().join("connections.ron")).unwrap();
+        for (name, folder, host) in [
+            ("orders-staging", "", "stage.internal"),
+            ("analytics", "reports", "dw.internal"),
+            ("orders-prod", "", "db1.internal"),
+        ] {
+            let connection = Connection {
+                name: name.to_string(),
+                folder: folder.to_string(),
+                host: host.to_string(),
+                ..Default::default()
+            };
+            store.add(connection).unwrap();
+        }
+        let mut tab = DbConnectionsTab::new(
+            Rc::new(RefCell::new(store)),
+            SharedHealth::default(),
+            SharedTheme::default(),
+        );
+        let (tx, rx) = mpsc::channel();
+        let key = |code| KeyEvent::new(code, KeyModifiers::NONE);
+
+        tab.handle_input(key(KeyCode::Char('/')), &tx).unwrap();
+        // neither "Create New" nor the folder header count as matches
+        assert_eq!(tab.match_count(), 3);
+        assert!(tab.handles_key(&key(KeyCode::Char('q'))));
+        // letters like `j` or `q` go into the filter instead of acting on the list
+        for c in "ordj".chars() {
+            tab.handle_input(key(KeyCode::Char(c)), &tx).unwrap();
+        }
+        assert_eq!(tab.filter_pattern(), "ordj");
+        tab.handle_input(key(KeyCode::Backspace), &tx).unwrap();
+        for c in "prod".chars() {
+            tab.handle_input(key(KeyCode::Char(c)), &tx).unwrap();
+        }
+        let ids: Vec<u32> = tab
+            .rows()
+            .iter()
+            .filter_map(|row| match row {
+                Row::Match { id, .. } => Some(*id),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(ids, vec![3]);
+        assert_eq!(tab.match_count(), 1);
+
+        // the host is searched too, but not the driver label or the folder
+        tab.filter = None;
+        tab.open_filter();
+        tab.handle_paste("reports");
+        assert_eq!(tab.match_count(), 0);
+        tab.filter = None;
+        tab.open_filter();
+        tab.handle_paste("PostgreSQL");
+        assert_eq!(tab.match_count(), 0);
+        tab.filter = None;
+        tab.open_filter();
+        tab.handle_paste("dw.int");
+        assert_eq!(tab.selected_connection_id(), Some(2));
+
+        tab.handle_input(key(KeyCode::Enter), &tx).unwrap();
+        assert!(matches!(rx.try_recv(), Ok(AppEvent::Connect { id: 2 })));
+        assert!(!tab.handles_key(&key(KeyCode::Char('q'))));
+        assert!(tab.handles_key(&key(KeyCode::Esc)));
+
+        // Esc goes back to the full list with the same connection selected
+        tab.handle_input(key(KeyCode::Esc), &tx).unwrap();
+        assert!(tab.filter.is_none());
+        assert_eq!(tab.selected_connection_id(), Some(2));
+    }
 }
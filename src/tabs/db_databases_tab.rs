@@ -1,34 +1,82 @@
-use std::{
-    io::{self, Result},
-    sync::mpsc,
-};
+use std::{io, sync::mpsc};
+
+use crossterm::event::KeyEvent;
+use ratatui::{prelude::*, style::Color, widgets::*};
 
-use crossterm::event::{KeyCode, KeyEvent};
-use ratatui::{
-    prelude::*,
-    style::Color,
-    widgets::{block::*, *},
+use crate::{
+    db::catalog::{DatabaseInfo, SharedCatalog},
+    AppEvent,
 };
 
-use crate::{App, AppEvent};
+use super::{
+    catalog_list::{CatalogEntry, CatalogList},
+    DBTab,
+};
 
-use super::DBTab;
+const KEY_HINTS: &str = "Enter: open | r: refresh";
 
 #[derive(Debug)]
 pub struct DbDatabasesTab {
     pub title: String,
+    /// Databases of the active session.
+    pub list: CatalogList<DatabaseInfo>,
     pub disabled: bool,
 }
 
-impl Default for DbDatabasesTab {
-    fn default() -> Self {
+impl DbDatabasesTab {
+    pub fn new(catalog: SharedCatalog) -> Self {
         Self {
             title: "Databases".to_string(),
+            list: CatalogList::new(
+                catalog,
+                "databases",
+                |catalog| &catalog.databases,
+                |name| AppEvent::SelectDatabase { name },
+                || AppEvent::LoadDatabases,
+            ),
             disabled: true,
         }
     }
 }
 
+impl CatalogEntry for DatabaseInfo {
+    const COLUMNS: &'static [(&'static str, Constraint)] = &[
+        ("Size", Constraint::Length(12)),
+        ("Owner", Constraint::Length(20)),
+    ];
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn is_current(&self) -> bool {
+        self.current
+    }
+
+    fn cells(&self) -> Vec<Cell<'static>> {
+        vec![
+            Cell::from(self.size.map(format_size).unwrap_or_default()),
+            Cell::from(self.owner.clone().unwrap_or_default()),
+        ]
+    }
+}
+
+/// Byte count with a binary unit, e.g. `8.1 MiB`.
+pub fn format_size(bytes: i64) -> String {
+    const UNITS: [&str; 5] = ["B", "KiB", "MiB", "GiB", "TiB"];
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} B", bytes)
+    } else {
+        format!("{:.1} {}", size, UNITS[unit])
+    }
+}
+
 impl DBTab for DbDatabasesTab {
     fn draw(&mut self, frame: &mut Frame, area: Rect) -> io::Result<()> {
         let chunks = Layout::default()
@@ -36,13 +84,14 @@ impl DBTab for DbDatabasesTab {
             .constraints([Constraint::Length(1), Constraint::Min(0)])
             .split(area);
 
-        Paragraph::new(Span::styled(
-            self.get_title(),
-            Style::default()
-                .fg(Color::White)
-                .add_modifier(Modifier::BOLD),
-        ))
-        .render(chunks[0], frame.buffer_mut());
+        frame.render_widget(
+            Paragraph::new(Span::styled(
+                KEY_HINTS,
+                Style::default().fg(Color::DarkGray),
+            )),
+            chunks[0],
+        );
+        self.list.draw(frame, chunks[1]);
 
         Ok(())
     }
@@ -52,6 +101,7 @@ impl DBTab for DbDatabasesTab {
         key: KeyEvent,
         app_event_bus: &mpsc::Sender<AppEvent>,
     ) -> io::Result<()> {
+        self.list.handle_input(key, app_event_bus);
         Ok(())
     }
 
@@ -67,3 +117,16 @@ impl DBTab for DbDatabasesTab {
         self.title.clone()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_format_size() {
+        assert_eq!(format_size(512), "512 B");
+        assert_eq!(format_size(8_500_000), "8.1 MiB");
+        assert_eq!(format_size(3 * 1024 * 1024 * 1024), "3.0 GiB");
+    }
+}
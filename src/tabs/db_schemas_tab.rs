@@ -0,0 +1,159 @@
+use std::{io, sync::mpsc};
+
+use crossterm::event::KeyEvent;
+use ratatui::{prelude::*, style::Color, widgets::*};
+
+use crate::{
+    db::catalog::{SchemaInfo, SharedCatalog},
+    AppEvent,
+};
+
+use super::{
+    catalog_list::{CatalogEntry, CatalogList},
+    db_databases_tab::format_size,
+    DBTab,
+};
+
+const KEY_HINTS: &str = "Enter: show tables | r: refresh";
+
+/// The schemas of the selected Postgres database. Left out of the tab bar for
+/// MySQL and SQLite, which go from databases straight to tables.
+#[derive(Debug)]
+pub struct DbSchemasTab {
+    pub title: String,
+    pub list: CatalogList<SchemaInfo>,
+    pub disabled: bool,
+}
+
+impl DbSchemasTab {
+    pub fn new(catalog: SharedCatalog) -> Self {
+        Self {
+            title: "Schemas".to_string(),
+            list: CatalogList::new(
+                catalog,
+                "schemas",
+                |catalog| &catalog.schemas,
+                |name| AppEvent::SelectSchema { name },
+                || AppEvent::LoadSchemas,
+            ),
+            disabled: true,
+        }
+    }
+}
+
+impl CatalogEntry for SchemaInfo {
+    const COLUMNS: &'static [(&'static str, Constraint)] = &[
+        ("Tables", Constraint::Length(8)),
+        ("Size", Constraint::Length(12)),
+        ("Owner", Constraint::Length(20)),
+    ];
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn is_current(&self) -> bool {
+        self.current
+    }
+
+    fn cells(&self) -> Vec<Cell<'static>> {
+        vec![
+            Cell::from(Text::from(self.tables.to_string()).alignment(Alignment::Right)),
+            Cell::from(
+                Text::from(self.size.map(format_size).unwrap_or_default())
+                    .alignment(Alignment::Right),
+            ),
+            Cell::from(self.owner.clone()),
+        ]
+    }
+}
+
+impl DBTab for DbSchemasTab {
+    fn draw(&mut self, frame: &mut Frame, area: Rect) -> io::Result<()> {
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(1), Constraint::Min(0)])
+            .split(area);
+
+        let Some(database) = self
+            .list
+            .catalog
+            .borrow()
+            .active()
+            .map(|catalog| catalog.selected_database.clone().unwrap_or_default())
+        else {
+            return Ok(());
+        };
+
+        frame.render_widget(
+            Paragraph::new(Line::from(vec![
+                Span::styled(
+                    database,
+                    Style::default()
+                        .fg(Color::White)
+                        .add_modifier(Modifier::BOLD),
+                ),
+                Span::styled(
+                    format!("  {}", KEY_HINTS),
+                    Style::default().fg(Color::DarkGray),
+                ),
+            ])),
+            chunks[0],
+        );
+        self.list.draw(frame, chunks[1]);
+
+        Ok(())
+    }
+
+    fn handle_input(
+        &mut self,
+        key: KeyEvent,
+        app_event_bus: &mpsc::Sender<AppEvent>,
+    ) -> io::Result<()> {
+        self.list.handle_input(key, app_event_bus);
+        Ok(())
+    }
+
+    fn is_disabled(&self) -> bool {
+        self.disabled
+    }
+
+    fn set_disabled(&mut self, disabled: bool) {
+        self.disabled = disabled;
+    }
+
+    fn is_hidden(&self) -> bool {
+        !self
+            .list
+            .catalog
+            .borrow()
+            .active()
+            .is_some_and(|catalog| catalog.has_schemas)
+    }
+
+    fn get_title(&self) -> String {
+        self.title.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::catalog::Loadable;
+
+    #[test]
+    fn test_hidden_without_schemas() {
+        let catalog = SharedCatalog::default();
+        let tab = DbSchemasTab::new(catalog.clone());
+        assert!(tab.is_hidden());
+
+        {
+            let mut catalog = catalog.borrow_mut();
+            catalog.active_session = Some(1);
+            let session = catalog.session_mut(1);
+            session.has_schemas = true;
+            session.schemas = Loadable::Loaded(vec![]);
+        }
+        assert!(!tab.is_hidden());
+    }
+}
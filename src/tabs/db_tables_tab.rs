@@ -1,32 +1,449 @@
-use std::{
-    io::{self, Result},
-    sync::mpsc,
-};
+use std::{cmp::Ordering, io, sync::mpsc};
 
 use crossterm::event::{KeyCode, KeyEvent};
-use ratatui::{
-    prelude::*,
-    style::Color,
-    widgets::{block::*, *},
+use ratatui::{prelude::*, style::Color, widgets::*};
+
+use crate::{
+    db::{
+        catalog::{ColumnInfo, Loadable, OpenTable, SharedCatalog, TableInfo, DATA_ROW_LIMIT},
+        pool::QueryResult,
+        table_keys::{ConstraintKind, ForeignKeyInfo, TableKeys},
+    },
+    AppEvent,
 };
 
-use crate::{App, AppEvent};
+use super::{db_databases_tab::format_size, DBTab};
+
+const KEY_HINTS: &str = "Enter: open | s: sort by next column | S: reverse | r: refresh";
+const DATA_HINTS: &str =
+    "Esc: back | 1: data | 2: structure | 3: indexes & keys | h/l: scroll columns | r: refresh";
+const PANE_HINTS: &str = "Esc: back | 1: data | 2: structure | 3: indexes & keys | r: refresh";
+
+/// Widest a column of table data is drawn, longer values are cut off.
+const MAX_DATA_COLUMN_WIDTH: usize = 40;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SortColumn {
+    #[default]
+    Name,
+    Kind,
+    Rows,
+    Size,
+}
+
+impl SortColumn {
+    fn next(self) -> Self {
+        match self {
+            SortColumn::Name => SortColumn::Kind,
+            SortColumn::Kind => SortColumn::Rows,
+            SortColumn::Rows => SortColumn::Size,
+            SortColumn::Size => SortColumn::Name,
+        }
+    }
 
-use super::DBTab;
+    fn compare(self, a: &TableInfo, b: &TableInfo) -> Ordering {
+        let by_name = || (&a.schema, &a.name).cmp(&(&b.schema, &b.name));
+        match self {
+            SortColumn::Name => by_name(),
+            SortColumn::Kind => a.kind.cmp(&b.kind).then_with(by_name),
+            SortColumn::Rows => a.rows.cmp(&b.rows).then_with(by_name),
+            SortColumn::Size => a.size.cmp(&b.size).then_with(by_name),
+        }
+    }
+}
+
+/// What is shown of the open table.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TablePane {
+    #[default]
+    Data,
+    Structure,
+    Keys,
+}
+
+impl TablePane {
+    fn as_str(&self) -> &'static str {
+        match self {
+            TablePane::Data => "Data",
+            TablePane::Structure => "Structure",
+            TablePane::Keys => "Indexes & Keys",
+        }
+    }
+
+    fn hints(&self) -> &'static str {
+        match self {
+            TablePane::Data => DATA_HINTS,
+            TablePane::Structure | TablePane::Keys => PANE_HINTS,
+        }
+    }
+}
 
 #[derive(Debug)]
 pub struct DbTablesTab {
     pub title: String,
+    /// Tables of the database selected in the Databases tab.
+    pub catalog: SharedCatalog,
+    pub table_state: TableState,
+    pub sort: SortColumn,
+    pub descending: bool,
+    /// Row selected in the data of the open table.
+    pub data_state: TableState,
+    /// First data column shown, for tables wider than the screen.
+    pub column_offset: usize,
+    pub pane: TablePane,
+    /// Column selected in the structure of the open table.
+    pub structure_state: TableState,
+    /// Lines scrolled down in the indexes and keys of the open table.
+    pub keys_scroll: u16,
     pub disabled: bool,
 }
 
-impl Default for DbTablesTab {
-    fn default() -> Self {
+impl DbTablesTab {
+    pub fn new(catalog: SharedCatalog) -> Self {
         Self {
             title: "Tables".to_string(),
+            catalog,
+            table_state: TableState::default().with_selected(Some(0)),
+            sort: SortColumn::default(),
+            descending: false,
+            data_state: TableState::default().with_selected(Some(0)),
+            column_offset: 0,
+            pane: TablePane::default(),
+            structure_state: TableState::default().with_selected(Some(0)),
+            keys_scroll: 0,
             disabled: true,
         }
     }
+
+    /// The tables in the order they are shown.
+    fn sorted_tables(&self) -> Vec<TableInfo> {
+        let mut tables = match self.catalog.borrow().active().map(|c| &c.tables) {
+            Some(Loadable::Loaded(tables)) => tables.clone(),
+            _ => vec![],
+        };
+        tables.sort_by(|a, b| {
+            let ordering = self.sort.compare(a, b);
+            if self.descending {
+                ordering.reverse()
+            } else {
+                ordering
+            }
+        });
+        tables
+    }
+
+    fn open_table(&self) -> Option<OpenTable> {
+        self.catalog
+            .borrow()
+            .active()
+            .and_then(|c| c.open_table.clone())
+    }
+
+    fn select(state: &mut TableState, len: usize, step: isize) {
+        if len == 0 {
+            return;
+        }
+        let current = state.selected().unwrap_or(0) as isize;
+        state.select(Some((current + step).rem_euclid(len as isize) as usize));
+    }
+
+    fn draw_tables(&mut self, frame: &mut Frame, area: Rect) {
+        let tables = self.sorted_tables();
+        if self.table_state.selected().unwrap_or(0) >= tables.len() {
+            self.table_state
+                .select(Some(tables.len().saturating_sub(1)));
+        }
+
+        let dimmed = Style::default().fg(Color::DarkGray);
+        let rows = tables.iter().map(|table| {
+            Row::new(vec![
+                // the list is already narrowed to the selected schema
+                Cell::from(Span::styled(
+                    table.name.clone(),
+                    Style::default().fg(Color::White),
+                )),
+                Cell::from(Span::styled(table.kind.as_str(), dimmed)),
+                Cell::from(
+                    Text::from(table.rows.map(|rows| rows.to_string()).unwrap_or_default())
+                        .alignment(Alignment::Right),
+                ),
+                Cell::from(
+                    Text::from(table.size.map(format_size).unwrap_or_default())
+                        .alignment(Alignment::Right),
+                ),
+            ])
+        });
+
+        let columns = [
+            (SortColumn::Name, "Name"),
+            (SortColumn::Kind, "Kind"),
+            (SortColumn::Rows, "Rows (est.)"),
+            (SortColumn::Size, "Size"),
+        ];
+        let header = Row::new(columns.map(|(column, label)| {
+            if column == self.sort {
+                let arrow = if self.descending {
+                    "\u{25BC}"
+                } else {
+                    "\u{25B2}"
+                };
+                Cell::from(format!("{} {}", label, arrow)).style(Style::default().fg(Color::Yellow))
+            } else {
+                Cell::from(label)
+            }
+        }))
+        .style(dimmed);
+
+        let table = Table::new(
+            rows,
+            [
+                Constraint::Min(20),
+                Constraint::Length(18),
+                Constraint::Length(13),
+                Constraint::Length(12),
+            ],
+        )
+        .header(header)
+        .highlight_style(Color::Yellow)
+        .highlight_spacing(HighlightSpacing::Always)
+        .highlight_symbol(">> ");
+
+        frame.render_stateful_widget(table, area, &mut self.table_state);
+    }
+
+    fn draw_data(&mut self, frame: &mut Frame, area: Rect, data: &QueryResult) {
+        if data.columns.is_empty() {
+            frame.render_widget(Paragraph::new("No rows."), area);
+            return;
+        }
+        self.column_offset = self.column_offset.min(data.columns.len() - 1);
+        if self.data_state.selected().unwrap_or(0) >= data.rows.len() {
+            self.data_state
+                .select(Some(data.rows.len().saturating_sub(1)));
+        }
+
+        let shown = self.column_offset..data.columns.len();
+        let widths: Vec<Constraint> = shown
+            .clone()
+            .map(|i| {
+                let width = data
+                    .rows
+                    .iter()
+                    .map(|row| row[i].as_deref().map_or(4, |value| value.chars().count()))
+                    .chain([data.columns[i].chars().count()])
+                    .max()
+                    .unwrap_or(0);
+                Constraint::Length(width.min(MAX_DATA_COLUMN_WIDTH) as u16)
+            })
+            .collect();
+
+        let null_style = Style::default().fg(Color::DarkGray);
+        let rows = data.rows.iter().map(|row| {
+            Row::new(row[shown.clone()].iter().map(|value| match value {
+                Some(value) => Cell::from(value.replace('\n', " ")),
+                None => Cell::from(Span::styled("NULL", null_style)),
+            }))
+        });
+        let header = Row::new(data.columns[shown.clone()].iter().map(String::as_str)).style(
+            Style::default()
+                .fg(Color::White)
+                .add_modifier(Modifier::BOLD),
+        );
+        let table = Table::new(rows, widths)
+            .header(header)
+            .column_spacing(2)
+            .highlight_style(Color::Yellow)
+            .highlight_spacing(HighlightSpacing::Always)
+            .highlight_symbol(">> ");
+
+        frame.render_stateful_widget(table, area, &mut self.data_state);
+    }
+
+    fn draw_structure(&mut self, frame: &mut Frame, area: Rect, columns: &[ColumnInfo]) {
+        if self.structure_state.selected().unwrap_or(0) >= columns.len() {
+            self.structure_state
+                .select(Some(columns.len().saturating_sub(1)));
+        }
+
+        let dimmed = Style::default().fg(Color::DarkGray);
+        let optional = |value: &Option<String>| Cell::from(value.clone().unwrap_or_default());
+        let rows = columns.iter().map(|column| {
+            Row::new(vec![
+                Cell::from(Span::styled(
+                    column.name.clone(),
+                    Style::default().fg(Color::White),
+                )),
+                Cell::from(column.data_type.clone()),
+                if column.nullable {
+                    Cell::from(Span::styled("NULL", dimmed))
+                } else {
+                    Cell::from("NOT NULL")
+                },
+                optional(&column.default),
+                optional(&column.generated),
+                optional(&column.collation),
+                Cell::from(Span::styled(
+                    column.comment.clone().unwrap_or_default(),
+                    dimmed,
+                )),
+            ])
+        });
+        let header = Row::new(vec![
+            "Column",
+            "Type",
+            "Null",
+            "Default",
+            "Generated",
+            "Collation",
+            "Comment",
+        ])
+        .style(dimmed);
+        let table = Table::new(
+            rows,
+            [
+                Constraint::Min(16),
+                Constraint::Min(16),
+                Constraint::Length(8),
+                Constraint::Min(12),
+                Constraint::Min(10),
+                Constraint::Length(12),
+                Constraint::Min(16),
+            ],
+        )
+        .header(header)
+        .highlight_style(Color::Yellow)
+        .highlight_spacing(HighlightSpacing::Always)
+        .highlight_symbol(">> ");
+
+        frame.render_stateful_widget(table, area, &mut self.structure_state);
+    }
+
+    fn draw_keys(&mut self, frame: &mut Frame, area: Rect, keys: &TableKeys) {
+        let lines = keys_lines(keys);
+        self.keys_scroll = self
+            .keys_scroll
+            .min(lines.len().saturating_sub(area.height as usize) as u16);
+        frame.render_widget(Paragraph::new(lines).scroll((self.keys_scroll, 0)), area);
+    }
+}
+
+/// The indexes, constraints and foreign keys of a table, one section each.
+fn keys_lines(keys: &TableKeys) -> Vec<Line<'static>> {
+    let heading = Style::default()
+        .fg(Color::White)
+        .add_modifier(Modifier::BOLD);
+    let name_style = Style::default().fg(Color::White);
+    let dimmed = Style::default().fg(Color::DarkGray);
+    let mut lines = vec![];
+    let mut section = |title: &str, items: Vec<Line<'static>>| {
+        if !lines.is_empty() {
+            lines.push(Line::default());
+        }
+        lines.push(Line::styled(title.to_string(), heading));
+        if items.is_empty() {
+            lines.push(Line::styled("  none", dimmed));
+        }
+        lines.extend(items);
+    };
+
+    section(
+        "Indexes",
+        keys.indexes
+            .iter()
+            .map(|index| {
+                let mut spans = vec![
+                    Span::raw("  "),
+                    Span::styled(index.name.clone(), name_style),
+                ];
+                if index.primary {
+                    spans.push(Span::styled(" PRIMARY", Style::default().fg(Color::Yellow)));
+                } else if index.unique {
+                    spans.push(Span::styled(" UNIQUE", Style::default().fg(Color::Cyan)));
+                }
+                if let Some(method) = &index.method {
+                    spans.push(Span::raw(format!(" {}", method)));
+                }
+                spans.push(Span::raw(format!(" ({})", index.columns.join(", "))));
+                if let Some(predicate) = &index.predicate {
+                    spans.push(Span::raw(format!(" WHERE {}", predicate)));
+                }
+                if let Some(size) = index.size {
+                    spans.push(Span::styled(format!("  {}", format_size(size)), dimmed));
+                }
+                Line::from(spans)
+            })
+            .collect(),
+    );
+    section(
+        "Constraints",
+        keys.constraints
+            .iter()
+            .map(|constraint| {
+                let style = if constraint.kind == ConstraintKind::PrimaryKey {
+                    Style::default().fg(Color::Yellow)
+                } else {
+                    Style::default()
+                };
+                let mut spans = vec![Span::raw("  ")];
+                if !constraint.name.is_empty() {
+                    spans.push(Span::styled(format!("{} ", constraint.name), name_style));
+                }
+                spans.push(Span::styled(constraint.definition.clone(), style));
+                Line::from(spans)
+            })
+            .collect(),
+    );
+    section(
+        "Foreign keys",
+        keys.outgoing
+            .iter()
+            .map(|key| foreign_key_line(key, true))
+            .collect(),
+    );
+    section(
+        "Referenced by",
+        keys.incoming
+            .iter()
+            .map(|key| foreign_key_line(key, false))
+            .collect(),
+    );
+    lines
+}
+
+/// `(customer_id) -> customers (id)` for keys of the table itself, the
+/// referencing table in front for keys pointing at it.
+fn foreign_key_line(key: &ForeignKeyInfo, outgoing: bool) -> Line<'static> {
+    let mut spans = vec![Span::raw("  ")];
+    if !key.name.is_empty() {
+        spans.push(Span::styled(
+            format!("{} ", key.name),
+            Style::default().fg(Color::White),
+        ));
+    }
+    let from = format!("({})", key.from_columns.join(", "));
+    let to = if key.to_columns.is_empty() {
+        String::new()
+    } else {
+        format!(" ({})", key.to_columns.join(", "))
+    };
+    spans.push(Span::raw(if outgoing {
+        format!("{} -> {}{}", from, key.to_table, to)
+    } else {
+        format!("{} {} -> {}", key.from_table, from, to.trim_start())
+    }));
+    spans.push(Span::styled(
+        format!("  ON UPDATE {} ON DELETE {}", key.on_update, key.on_delete),
+        Style::default().fg(Color::DarkGray),
+    ));
+    Line::from(spans)
+}
+
+/// `schema.name` for Postgres tables, the bare name otherwise.
+pub fn display_name(table: &TableInfo) -> String {
+    match table.schema.as_deref() {
+        Some(schema) => format!("{}.{}", schema, table.name),
+        None => table.name.clone(),
+    }
 }
 
 impl DBTab for DbTablesTab {
@@ -36,13 +453,87 @@ impl DBTab for DbTablesTab {
             .constraints([Constraint::Length(1), Constraint::Min(0)])
             .split(area);
 
-        Paragraph::new(Span::styled(
-            self.get_title(),
+        let (database, schema, tables, open_table) = match self.catalog.borrow().active() {
+            Some(catalog) => (
+                catalog.selected_database.clone().unwrap_or_default(),
+                catalog.selected_schema.clone(),
+                catalog.tables.clone(),
+                catalog.open_table.clone(),
+            ),
+            None => return Ok(()),
+        };
+
+        let dimmed = Style::default().fg(Color::DarkGray);
+        let mut breadcrumb = vec![Span::styled(
+            database,
             Style::default()
                 .fg(Color::White)
                 .add_modifier(Modifier::BOLD),
-        ))
-        .render(chunks[0], frame.buffer_mut());
+        )];
+        if let Some(schema) = schema {
+            breadcrumb.push(Span::styled(" > ", dimmed));
+            breadcrumb.push(Span::styled(schema, Style::default().fg(Color::White)));
+        }
+        if let Some(open_table) = &open_table {
+            breadcrumb.push(Span::styled(" > ", dimmed));
+            breadcrumb.push(Span::styled(
+                open_table.table.name.clone(),
+                Style::default().fg(Color::White),
+            ));
+            breadcrumb.push(Span::styled(" > ", dimmed));
+            breadcrumb.push(Span::styled(
+                self.pane.as_str(),
+                Style::default().fg(Color::White),
+            ));
+            if self.pane == TablePane::Data {
+                breadcrumb.push(Span::styled(
+                    format!(" (first {} rows)", DATA_ROW_LIMIT),
+                    dimmed,
+                ));
+            }
+        }
+        let hints = if open_table.is_some() {
+            self.pane.hints()
+        } else {
+            KEY_HINTS
+        };
+        breadcrumb.push(Span::styled(format!("  {}", hints), dimmed));
+        frame.render_widget(Paragraph::new(Line::from(breadcrumb)), chunks[0]);
+
+        let (loadable, what) = match open_table {
+            Some(open_table) => match self.pane {
+                TablePane::Data => (
+                    open_table.data.map(Content::Data),
+                    format!("the rows of {}", display_name(&open_table.table)),
+                ),
+                TablePane::Structure => (
+                    open_table.columns.map(Content::Columns),
+                    format!("the columns of {}", display_name(&open_table.table)),
+                ),
+                TablePane::Keys => (
+                    open_table.keys.map(Content::Keys),
+                    format!("the indexes of {}", display_name(&open_table.table)),
+                ),
+            },
+            None => (tables.map(|_| Content::Tables), "the tables".to_string()),
+        };
+        match loadable {
+            Loadable::Loaded(Content::Tables) => self.draw_tables(frame, chunks[1]),
+            Loadable::Loaded(Content::Data(data)) => self.draw_data(frame, chunks[1], &data),
+            Loadable::Loaded(Content::Columns(columns)) => {
+                self.draw_structure(frame, chunks[1], &columns)
+            }
+            Loadable::Loaded(Content::Keys(keys)) => self.draw_keys(frame, chunks[1], &keys),
+            Loadable::Failed(error) => frame.render_widget(
+                Paragraph::new(format!("Failed to load {}: {}", what, error))
+                    .style(Style::default().fg(Color::Red))
+                    .wrap(Wrap { trim: false }),
+                chunks[1],
+            ),
+            Loadable::Loading => {
+                frame.render_widget(Paragraph::new(format!("Loading {}...", what)), chunks[1])
+            }
+        }
 
         Ok(())
     }
@@ -52,9 +543,82 @@ impl DBTab for DbTablesTab {
         key: KeyEvent,
         app_event_bus: &mpsc::Sender<AppEvent>,
     ) -> io::Result<()> {
+        if let Some(open_table) = self.open_table() {
+            let data = match &open_table.data {
+                Loadable::Loaded(data) if self.pane == TablePane::Data => Some(data),
+                _ => None,
+            };
+            let rows = data.map_or(0, |data| data.rows.len());
+            let columns = data.map_or(0, |data| data.columns.len());
+            let structure_rows = match &open_table.columns {
+                Loadable::Loaded(columns) => columns.len(),
+                _ => 0,
+            };
+            match key.code {
+                KeyCode::Esc | KeyCode::Backspace => {
+                    app_event_bus.send(AppEvent::CloseTable).unwrap()
+                }
+                KeyCode::Char('r') => app_event_bus
+                    .send(AppEvent::OpenTable {
+                        table: open_table.table,
+                    })
+                    .unwrap(),
+                KeyCode::Char('1') => self.pane = TablePane::Data,
+                KeyCode::Char('2') => self.pane = TablePane::Structure,
+                KeyCode::Char('3') => self.pane = TablePane::Keys,
+                KeyCode::Down | KeyCode::Char('j') => match self.pane {
+                    TablePane::Data => Self::select(&mut self.data_state, rows, 1),
+                    TablePane::Structure => {
+                        Self::select(&mut self.structure_state, structure_rows, 1)
+                    }
+                    // draw_keys stops at the last line
+                    TablePane::Keys => self.keys_scroll = self.keys_scroll.saturating_add(1),
+                },
+                KeyCode::Up | KeyCode::Char('k') => match self.pane {
+                    TablePane::Data => Self::select(&mut self.data_state, rows, -1),
+                    TablePane::Structure => {
+                        Self::select(&mut self.structure_state, structure_rows, -1)
+                    }
+                    TablePane::Keys => self.keys_scroll = self.keys_scroll.saturating_sub(1),
+                },
+                KeyCode::Right | KeyCode::Char('l') if self.column_offset + 1 < columns => {
+                    self.column_offset += 1
+                }
+                KeyCode::Left | KeyCode::Char('h') => {
+                    self.column_offset = self.column_offset.saturating_sub(1)
+                }
+                _ => {}
+            }
+            return Ok(());
+        }
+
+        let len = self.sorted_tables().len();
+        match key.code {
+            KeyCode::Enter => {
+                let selected = self.table_state.selected().unwrap_or(0);
+                if let Some(table) = self.sorted_tables().into_iter().nth(selected) {
+                    self.data_state.select(Some(0));
+                    self.structure_state.select(Some(0));
+                    self.keys_scroll = 0;
+                    self.column_offset = 0;
+                    app_event_bus.send(AppEvent::OpenTable { table }).unwrap();
+                }
+            }
+            KeyCode::Char('s') => self.sort = self.sort.next(),
+            KeyCode::Char('S') => self.descending = !self.descending,
+            KeyCode::Char('r') => app_event_bus.send(AppEvent::LoadTables).unwrap(),
+            KeyCode::Down | KeyCode::Char('j') => Self::select(&mut self.table_state, len, 1),
+            KeyCode::Up | KeyCode::Char('k') => Self::select(&mut self.table_state, len, -1),
+            _ => {}
+        }
         Ok(())
     }
 
+    /// Esc goes back from a table's data to the list instead of closing the app.
+    fn handles_key(&self, key: &KeyEvent) -> bool {
+        key.code == KeyCode::Esc && self.open_table().is_some()
+    }
+
     fn is_disabled(&self) -> bool {
         self.disabled
     }
@@ -67,3 +631,105 @@ impl DBTab for DbTablesTab {
         self.title.clone()
     }
 }
+
+/// What the lower part of the tab shows once loaded.
+enum Content {
+    Tables,
+    Data(QueryResult),
+    Columns(Vec<ColumnInfo>),
+    Keys(TableKeys),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::catalog::TableKind;
+    use crossterm::event::KeyModifiers;
+    use pretty_assertions::assert_eq;
+
+    fn table(name: &str, kind: TableKind, rows: Option<i64>) -> TableInfo {
+        TableInfo {
+            schema: Some("public".to_string()),
+            name: name.to_string(),
+            kind,
+            rows,
+            size: None,
+        }
+    }
+
+    #[test]
+    fn test_sort_and_open_table() {
+        let catalog = SharedCatalog::default();
+        {
+            let mut catalog = catalog.borrow_mut();
+            catalog.active_session = Some(1);
+            catalog.session_mut(1).tables = Loadable::Loaded(vec![
+                table("accounts", TableKind::Table, Some(10)),
+                table("active_users", TableKind::View, None),
+                table("orders", TableKind::Table, Some(5000)),
+            ]);
+        }
+        let mut tab = DbTablesTab::new(catalog);
+        let (tx, rx) = mpsc::channel();
+        let key = |code| KeyEvent::new(code, KeyModifiers::NONE);
+        let names = |tab: &DbTablesTab| -> Vec<String> {
+            tab.sorted_tables().into_iter().map(|t| t.name).collect()
+        };
+
+        // name -> kind -> rows
+        tab.handle_input(key(KeyCode::Char('s')), &tx).unwrap();
+        tab.handle_input(key(KeyCode::Char('s')), &tx).unwrap();
+        tab.handle_input(key(KeyCode::Char('S')), &tx).unwrap();
+        assert_eq!(names(&tab), vec!["orders", "accounts", "active_users"]);
+
+        tab.handle_input(key(KeyCode::Enter), &tx).unwrap();
+        assert!(matches!(
+            rx.try_recv(),
+            Ok(AppEvent::OpenTable { table }) if table.name == "orders"
+        ));
+    }
+
+    #[test]
+    fn test_foreign_key_line() {
+        let key = ForeignKeyInfo {
+            name: "orders_customer_fk".to_string(),
+            from_table: "orders".to_string(),
+            from_columns: vec!["customer_id".to_string()],
+            to_table: "customers".to_string(),
+            to_columns: vec!["id".to_string()],
+            on_update: "NO ACTION".to_string(),
+            on_delete: "CASCADE".to_string(),
+        };
+        let text = |line: Line| -> String {
+            line.spans
+                .iter()
+                .map(|span| span.content.as_ref())
+                .collect()
+        };
+        assert_eq!(
+            text(foreign_key_line(&key, true)),
+            "  orders_customer_fk (customer_id) -> customers (id)  ON UPDATE NO ACTION ON DELETE CASCADE"
+        );
+        assert_eq!(
+            text(foreign_key_line(&key, false)),
+            "  orders_customer_fk orders (customer_id) -> (id)  ON UPDATE NO ACTION ON DELETE CASCADE"
+        );
+
+        let lines = keys_lines(&TableKeys {
+            outgoing: vec![key],
+            ..Default::default()
+        });
+        assert_eq!(text(lines[0].clone()), "Indexes");
+        assert_eq!(text(lines[1].clone()), "  none");
+    }
+
+    #[test]
+    fn test_display_name() {
+        let mut events = table("events", TableKind::Table, None);
+        assert_eq!(display_name(&events), "public.events");
+        events.schema = Some("audit".to_string());
+        assert_eq!(display_name(&events), "audit.events");
+        events.schema = None;
+        assert_eq!(display_name(&events), "events");
+    }
+}
@@ -1,16 +1,11 @@
-use std::{
-    io::{self, Result},
-    sync::mpsc,
-};
+use std::{io::Result, sync::mpsc};
 
 use crossterm::event::{KeyCode, KeyEvent};
-use ratatui::{
-    prelude::*,
-    style::Color,
-    widgets::{block::*, *},
-};
+use ratatui::{prelude::*, style::Color, widgets::*};
 
-use crate::{App, AppEvent};
+use serde::{Deserialize, Serialize};
+
+use crate::AppEvent;
 
 use super::DBTab;
 
@@ -23,8 +18,9 @@ pub struct DbTypesTab {
     pub disabled: bool,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
 pub enum DBTypes {
+    #[default]
     POSTGRES,
     MYSQL,
     MARIA,
@@ -45,6 +41,32 @@ impl DBTypes {
             DBTypes::SQLITE => SQLITE_STR,
         }
     }
+
+    /// URL scheme understood by sqlx for this driver.
+    pub fn url_scheme(&self) -> &'static str {
+        match *self {
+            DBTypes::POSTGRES => "postgres",
+            DBTypes::MYSQL | DBTypes::MARIA => "mysql",
+            DBTypes::SQLITE => "sqlite",
+        }
+    }
+
+    pub fn default_port(&self) -> u16 {
+        match *self {
+            DBTypes::POSTGRES => 5432,
+            DBTypes::MYSQL | DBTypes::MARIA => 3306,
+            DBTypes::SQLITE => 0,
+        }
+    }
+
+    /// URL query parameter sqlx reads a unix socket path from, `None` for SQLite.
+    pub fn socket_param(&self) -> Option<&'static str> {
+        match *self {
+            DBTypes::POSTGRES => Some("host"),
+            DBTypes::MYSQL | DBTypes::MARIA => Some("socket"),
+            DBTypes::SQLITE => None,
+        }
+    }
 }
 
 impl Default for DbTypesTab {
@@ -5,8 +5,10 @@ use ratatui::{layout::Rect, Frame};
 
 use crate::AppEvent;
 
+pub mod catalog_list;
 pub mod db_connections_tab;
 pub mod db_databases_tab;
+pub mod db_schemas_tab;
 pub mod db_tables_tab;
 pub mod db_types_tab;
 
@@ -20,4 +22,18 @@ pub trait DBTab {
     fn is_disabled(&self) -> bool;
     fn set_disabled(&mut self, disabled: bool);
     fn get_title(&self) -> String;
+
+    /// Whether the tab is left out of the tab bar altogether, e.g. a level of
+    /// the catalog the connected database does not have.
+    fn is_hidden(&self) -> bool {
+        false
+    }
+
+    /// Whether the tab takes `key` before the app-wide shortcuts (`q`, `Esc`,
+    /// `Tab`, `:`), e.g. while text is typed into it.
+    fn handles_key(&self, _key: &KeyEvent) -> bool {
+        false
+    }
+
+    fn handle_paste(&mut self, _text: &str) {}
 }
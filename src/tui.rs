@@ -1,6 +1,10 @@
 use std::io::{self, stdout, Stdout};
 
-use crossterm::{execute, terminal::*};
+use crossterm::{
+    event::{DisableBracketedPaste, EnableBracketedPaste},
+    execute,
+    terminal::*,
+};
 use ratatui::prelude::*;
 
 // A type alias for the terminal type used in this application
@@ -8,12 +12,12 @@ pub type Tui = Terminal<CrosstermBackend<Stdout>>;
 
 // Initialize the terminal
 pub fn init() -> io::Result<Tui> {
-    execute!(stdout(), EnterAlternateScreen)?;
+    execute!(stdout(), EnterAlternateScreen, EnableBracketedPaste)?;
     enable_raw_mode()?;
     Terminal::new(CrosstermBackend::new(stdout()))
 }
 
 pub fn restore() -> io::Result<()> {
     disable_raw_mode()?;
-    execute!(stdout(), LeaveAlternateScreen)
+    execute!(stdout(), DisableBracketedPaste, LeaveAlternateScreen)
 }
@@ -0,0 +1,80 @@
+use std::{io, sync::mpsc};
+
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{
+    layout::{Alignment, Constraint, Direction, Layout, Rect},
+    style::{Color, Style},
+    widgets::{Block, Borders, Clear, Paragraph, Wrap},
+    Frame,
+};
+
+use crate::AppEvent;
+
+use super::centered_rect_exact_height;
+
+const FOOTER_TEXT: &str = "Y or Enter: confirm | N or Esc: Cancel";
+
+/// Asks a yes/no question and sends `on_confirm` when the user agrees.
+#[derive(Debug)]
+pub struct ConfirmPopup {
+    title: String,
+    message: String,
+    on_confirm: Option<Box<AppEvent>>,
+}
+
+impl ConfirmPopup {
+    pub fn new(title: impl Into<String>, message: impl Into<String>, on_confirm: AppEvent) -> Self {
+        Self {
+            title: title.into(),
+            message: message.into(),
+            on_confirm: Some(Box::new(on_confirm)),
+        }
+    }
+
+    pub fn render_widget(&mut self, frame: &mut Frame, area: Rect) {
+        let area = centered_rect_exact_height(50, 8, area);
+
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(Color::Yellow))
+            .title(self.title.as_str());
+
+        frame.render_widget(Clear, area);
+        frame.render_widget(block, area);
+
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .horizontal_margin(2)
+            .vertical_margin(1)
+            .constraints([Constraint::Min(3), Constraint::Length(1)].as_ref())
+            .split(area);
+
+        let message = Paragraph::new(self.message.as_str())
+            .alignment(Alignment::Center)
+            .wrap(Wrap { trim: false });
+        frame.render_widget(message, chunks[0]);
+
+        let footer = Paragraph::new(FOOTER_TEXT).alignment(Alignment::Center);
+        frame.render_widget(footer, chunks[1]);
+    }
+
+    pub fn handle_input(
+        &mut self,
+        key_event: &KeyEvent,
+        app_event_bus: &mpsc::Sender<AppEvent>,
+    ) -> io::Result<()> {
+        match key_event.code {
+            KeyCode::Esc | KeyCode::Char('n') => {
+                app_event_bus.send(AppEvent::CancelClosePopup).unwrap();
+            }
+            KeyCode::Enter | KeyCode::Char('y') => {
+                app_event_bus.send(AppEvent::CancelClosePopup).unwrap();
+                if let Some(event) = self.on_confirm.take() {
+                    app_event_bus.send(*event).unwrap();
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,158 @@
+use ratatui::{
+    style::{Color, Modifier, Style},
+    text::Span,
+};
+
+/// Result of matching a pattern against a candidate.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FuzzyMatch {
+    /// Higher is better.
+    pub score: i64,
+    /// Char positions in the candidate that matched, for highlighting.
+    pub indices: Vec<usize>,
+}
+
+const MATCH_SCORE: i64 = 16;
+const CONSECUTIVE_BONUS: i64 = 16;
+const WORD_START_BONUS: i64 = 8;
+const GAP_PENALTY: i64 = 1;
+
+/// Matches the chars of `pattern` in order (case-insensitive, spaces ignored)
+/// against `candidate`. Consecutive chars and chars at the start of a word
+/// score higher, gaps score lower. `None` when not all chars are found.
+pub fn fuzzy_match(pattern: &str, candidate: &str) -> Option<FuzzyMatch> {
+    let pattern: Vec<char> = pattern
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .flat_map(char::to_lowercase)
+        .collect();
+    let chars: Vec<char> = candidate.chars().collect();
+
+    let Some(first) = pattern.first() else {
+        return Some(FuzzyMatch {
+            score: 0,
+            indices: vec![],
+        });
+    };
+
+    // try every place the first char occurs, the earliest is not always the best
+    (0..chars.len())
+        .filter(|start| matches_char(chars[*start], *first))
+        .filter_map(|start| match_from(&pattern, &chars, start))
+        .max_by_key(|fuzzy_match| fuzzy_match.score)
+}
+
+fn matches_char(c: char, wanted: char) -> bool {
+    c.to_lowercase().eq(Some(wanted))
+}
+
+/// Greedily matches `pattern` with its first char at `start`.
+fn match_from(pattern: &[char], chars: &[char], start: usize) -> Option<FuzzyMatch> {
+    // text skipped before the match counts as a gap too
+    let mut score = -(start as i64) * GAP_PENALTY;
+    let mut indices = Vec::with_capacity(pattern.len());
+    let mut next = start;
+    for wanted in pattern {
+        let found = (next..chars.len()).find(|i| matches_char(chars[*i], *wanted))?;
+
+        score += MATCH_SCORE;
+        if indices.last().is_some_and(|last| last + 1 == found) {
+            score += CONSECUTIVE_BONUS;
+        } else {
+            score -= (found - next) as i64 * GAP_PENALTY;
+        }
+        if found == 0 || !chars[found - 1].is_alphanumeric() {
+            score += WORD_START_BONUS;
+        }
+        indices.push(found);
+        next = found + 1;
+    }
+
+    Some(FuzzyMatch { score, indices })
+}
+
+/// Filters `candidates` by `pattern` and sorts them best match first. An empty
+/// pattern keeps every candidate in its original order.
+pub fn fuzzy_filter<'a, T>(
+    pattern: &str,
+    candidates: impl IntoIterator<Item = (T, &'a str)>,
+) -> Vec<(T, FuzzyMatch)> {
+    let mut matches: Vec<(T, FuzzyMatch)> = candidates
+        .into_iter()
+        .filter_map(|(item, text)| fuzzy_match(pattern, text).map(|m| (item, m)))
+        .collect();
+    // stable, so equal scores keep their order
+    matches.sort_by_key(|(_, fuzzy_match)| std::cmp::Reverse(fuzzy_match.score));
+    matches
+}
+
+/// Splits `text` into spans in `style`, with the chars at `indices` (as
+/// returned by [`fuzzy_match`]) underlined in yellow.
+pub fn highlighted<'a>(text: &str, indices: &[usize], style: Style) -> Vec<Span<'a>> {
+    let matched_style = style.fg(Color::Yellow).add_modifier(Modifier::UNDERLINED);
+    let mut spans = vec![];
+    let mut run = String::new();
+    let mut run_matched = false;
+    for (i, c) in text.chars().enumerate() {
+        let matched = indices.contains(&i);
+        if matched != run_matched && !run.is_empty() {
+            let run_style = if run_matched { matched_style } else { style };
+            spans.push(Span::styled(std::mem::take(&mut run), run_style));
+        }
+        run_matched = matched;
+        run.push(c);
+    }
+    if !run.is_empty() {
+        spans.push(Span::styled(
+            run,
+            if run_matched { matched_style } else { style },
+        ));
+    }
+    spans
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_fuzzy_match() {
+        let m = fuzzy_match("stg", "staging-db").unwrap();
+        assert_eq!(m.indices, vec![0, 1, 3]);
+        assert!(fuzzy_match("STG", "staging-db").is_some());
+        assert!(fuzzy_match("gts", "staging-db").is_none());
+        assert!(fuzzy_match("", "anything").unwrap().indices.is_empty());
+    }
+
+    #[test]
+    fn test_fuzzy_filter_ranks_better_matches_first() {
+        let candidates = vec![
+            (1, "app-production-replica"),
+            (2, "prod"),
+            (3, "staging"),
+            (4, "p r o d"),
+        ];
+
+        let ranked: Vec<i32> = fuzzy_filter("prod", candidates.clone())
+            .into_iter()
+            .map(|(id, _)| id)
+            .collect();
+        assert_eq!(ranked, vec![2, 1, 4]);
+
+        let all: Vec<i32> = fuzzy_filter("", candidates)
+            .into_iter()
+            .map(|(id, _)| id)
+            .collect();
+        assert_eq!(all, vec![1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn test_highlighted_groups_runs() {
+        let spans = highlighted("staging", &[0, 1, 3], Style::default());
+        let texts: Vec<&str> = spans.iter().map(|span| span.content.as_ref()).collect();
+        assert_eq!(texts, vec!["st", "a", "g", "ing"]);
+        assert_eq!(spans[1].style, Style::default());
+        assert!(spans[2].style.add_modifier.contains(Modifier::UNDERLINED));
+    }
+}
@@ -0,0 +1,179 @@
+use std::{io, sync::mpsc};
+
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{
+    layout::{Alignment, Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph},
+    Frame,
+};
+
+use crate::{
+    db::{connection::Connection, import::ImportCandidate},
+    AppEvent,
+};
+
+use super::centered_rect_exact_height;
+
+const FOOTER_TEXT: &str = "Space: select | a: select all/none | Enter: import | Esc: cancel";
+
+/// Previews the connections found in the client config files and lets the
+/// user pick the ones to add.
+#[derive(Debug)]
+pub struct ImportPopup {
+    candidates: Vec<ImportCandidate>,
+    /// Whether each candidate is picked for import.
+    selected: Vec<bool>,
+    /// Candidates matching a connection that is saved already.
+    existing: Vec<bool>,
+    state: ListState,
+}
+
+impl ImportPopup {
+    /// `existing[i]` marks candidates that are saved already, they start unselected.
+    pub fn new(candidates: Vec<ImportCandidate>, existing: Vec<bool>) -> Self {
+        Self {
+            selected: existing.iter().map(|exists| !exists).collect(),
+            candidates,
+            existing,
+            state: ListState::default().with_selected(Some(0)),
+        }
+    }
+
+    fn toggle_selected(&mut self) {
+        if let Some(selected) = self.state.selected().and_then(|i| self.selected.get_mut(i)) {
+            *selected = !*selected;
+        }
+    }
+
+    fn toggle_all(&mut self) {
+        let select = !self.selected.iter().all(|selected| *selected);
+        self.selected
+            .iter_mut()
+            .for_each(|selected| *selected = select);
+    }
+
+    fn picked(&self) -> Vec<Connection> {
+        self.candidates
+            .iter()
+            .zip(&self.selected)
+            .filter(|(_, selected)| **selected)
+            .map(|(candidate, _)| candidate.connection.clone())
+            .collect()
+    }
+
+    pub fn render_widget(&mut self, frame: &mut Frame, area: Rect) {
+        let height = self.candidates.len() as u16 + 6;
+        let area = centered_rect_exact_height(80, height.max(10), area);
+
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title(" Import Connections ");
+
+        frame.render_widget(Clear, area);
+        frame.render_widget(block, area);
+
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .horizontal_margin(2)
+            .vertical_margin(1)
+            .constraints(
+                [
+                    Constraint::Length(1),
+                    Constraint::Min(1),
+                    Constraint::Length(1),
+                ]
+                .as_ref(),
+            )
+            .split(area);
+
+        let count = self.selected.iter().filter(|selected| **selected).count();
+        frame.render_widget(
+            Paragraph::new(format!(
+                "Found {} connections, {} selected. Passwords stay in the files and are read on connect.",
+                self.candidates.len(),
+                count
+            ))
+            .alignment(Alignment::Center),
+            chunks[0],
+        );
+
+        let dimmed = Style::default().fg(Color::DarkGray);
+        let items: Vec<ListItem> = self
+            .candidates
+            .iter()
+            .enumerate()
+            .map(|(i, candidate)| {
+                let connection = &candidate.connection;
+                let mut spans = vec![
+                    Span::raw(if self.selected[i] { "[x] " } else { "[ ] " }),
+                    Span::styled(connection.name.clone(), Style::default().fg(Color::White)),
+                    Span::styled(
+                        format!(
+                            "  [{}] {}",
+                            connection.db_type.as_str(),
+                            connection.summary()
+                        ),
+                        dimmed,
+                    ),
+                    Span::styled(format!("  {}", candidate.source), dimmed),
+                ];
+                if !candidate.has_password {
+                    spans.push(Span::styled(
+                        "  no password",
+                        Style::default().fg(Color::Yellow),
+                    ));
+                }
+                if self.existing[i] {
+                    spans.push(Span::styled(
+                        "  already saved",
+                        Style::default().fg(Color::Cyan),
+                    ));
+                }
+                ListItem::new(Line::from(spans))
+            })
+            .collect();
+        let list = List::new(items)
+            .highlight_style(Style::default().add_modifier(Modifier::REVERSED))
+            .highlight_symbol("> ");
+        frame.render_stateful_widget(list, chunks[1], &mut self.state);
+
+        let footer = Paragraph::new(FOOTER_TEXT).alignment(Alignment::Center);
+        frame.render_widget(footer, chunks[2]);
+    }
+
+    pub fn handle_input(
+        &mut self,
+        key_event: &KeyEvent,
+        app_event_bus: &mpsc::Sender<AppEvent>,
+    ) -> io::Result<()> {
+        let len = self.candidates.len();
+        match key_event.code {
+            KeyCode::Esc | KeyCode::Char('q') => {
+                app_event_bus.send(AppEvent::CancelClosePopup).unwrap()
+            }
+            KeyCode::Enter => {
+                let connections = self.picked();
+                if !connections.is_empty() {
+                    app_event_bus.send(AppEvent::CancelClosePopup).unwrap();
+                    app_event_bus
+                        .send(AppEvent::ImportConnections { connections })
+                        .unwrap();
+                }
+            }
+            KeyCode::Char(' ') => self.toggle_selected(),
+            KeyCode::Char('a') => self.toggle_all(),
+            KeyCode::Down | KeyCode::Char('j') if len > 0 => {
+                let i = self.state.selected().map_or(0, |i| (i + 1) % len);
+                self.state.select(Some(i));
+            }
+            KeyCode::Up | KeyCode::Char('k') if len > 0 => {
+                let i = self.state.selected().map_or(0, |i| (i + len - 1) % len);
+                self.state.select(Some(i));
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+}
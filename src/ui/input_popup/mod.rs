@@ -0,0 +1,318 @@
+use std::{io, sync::mpsc};
+
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{
+    layout::{Alignment, Constraint, Direction, Layout, Rect},
+    style::{Color, Style},
+    widgets::{Block, Borders, Clear, Paragraph},
+    Frame,
+};
+use tui_textarea::TextArea;
+
+use crate::AppEvent;
+
+use super::centered_rect_exact_height;
+
+const FOOTER_TEXT: &str = "Enter: confirm | Esc: Cancel";
+
+/// What the entered text is used for once the popup is confirmed.
+#[derive(Debug, Clone)]
+pub enum InputPurpose {
+    RenameConnection {
+        id: u32,
+    },
+    UnlockCredentials,
+    CreateCredentials,
+    /// The new master passphrase typed a second time, it must match `passphrase`.
+    RepeatPassphrase {
+        passphrase: String,
+    },
+    ConnectionPassword {
+        id: u32,
+    },
+    ExecuteSql,
+    /// Runs `sql` once the user typed `expected` (the connection name).
+    ConfirmProductionWrite {
+        sql: String,
+        expected: String,
+    },
+}
+
+impl InputPurpose {
+    fn to_event(&self, value: String) -> AppEvent {
+        match self {
+            InputPurpose::RenameConnection { id } => AppEvent::RenameConnection {
+                id: *id,
+                name: value,
+            },
+            InputPurpose::UnlockCredentials => AppEvent::UnlockCredentials { passphrase: value },
+            InputPurpose::CreateCredentials => AppEvent::PassphraseEntered { passphrase: value },
+            InputPurpose::RepeatPassphrase { passphrase } => AppEvent::CreateCredentials {
+                passphrase: passphrase.clone(),
+            },
+            InputPurpose::ConnectionPassword { id } => AppEvent::ConnectWithPassword {
+                id: *id,
+                password: value,
+            },
+            InputPurpose::ExecuteSql => AppEvent::ExecuteSql {
+                sql: value,
+                confirmed: false,
+            },
+            InputPurpose::ConfirmProductionWrite { sql, .. } => AppEvent::ExecuteSql {
+                sql: sql.clone(),
+                confirmed: true,
+            },
+        }
+    }
+
+    /// Sent after the popup is closed with Esc.
+    fn cancel_event(&self) -> Option<AppEvent> {
+        match self {
+            InputPurpose::UnlockCredentials
+            | InputPurpose::CreateCredentials
+            | InputPurpose::RepeatPassphrase { .. } => Some(AppEvent::CredentialsDismissed),
+            _ => None,
+        }
+    }
+
+    fn validate(&self, value: &str) -> Result<(), String> {
+        match self {
+            InputPurpose::UnlockCredentials | InputPurpose::CreateCredentials
+                if value.is_empty() =>
+            {
+                Err("Passphrase cannot be empty.".to_string())
+            }
+            InputPurpose::RepeatPassphrase { passphrase } if value != passphrase => {
+                Err("Passphrases do not match.".to_string())
+            }
+            InputPurpose::ConfirmProductionWrite { expected, .. } if value != expected => {
+                Err(format!("Type \"{}\" to confirm.", expected))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Single line text prompt.
+#[derive(Debug)]
+pub struct InputPopup<'a> {
+    title: String,
+    hint: String,
+    input: TextArea<'a>,
+    purpose: InputPurpose,
+    err_msg: String,
+}
+
+impl<'a> InputPopup<'a> {
+    pub fn new(title: impl Into<String>, initial_value: &str, purpose: InputPurpose) -> Self {
+        let mut input = TextArea::new(vec![initial_value.to_string()]);
+        input.move_cursor(tui_textarea::CursorMove::End);
+        input.set_cursor_line_style(Style::default());
+        input.set_cursor_style(Style::default().bg(Color::White).fg(Color::Black));
+
+        Self {
+            title: title.into(),
+            hint: String::new(),
+            input,
+            purpose,
+            err_msg: String::new(),
+        }
+    }
+
+    /// Same as `new` but the typed text is shown as bullets.
+    pub fn masked(title: impl Into<String>, purpose: InputPurpose) -> Self {
+        let mut popup = Self::new(title, "", purpose);
+        popup.input.set_mask_char('\u{2022}'); // U+2022 BULLET (•)
+        popup
+    }
+
+    /// Adds an explanatory line above the input.
+    pub fn with_hint(mut self, hint: impl Into<String>) -> Self {
+        self.hint = hint.into();
+        self
+    }
+
+    pub fn title(&self) -> &str {
+        &self.title
+    }
+
+    pub fn render_widget(&mut self, frame: &mut Frame, area: Rect) {
+        let area = centered_rect_exact_height(50, 8, area);
+
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title(self.title.as_str());
+
+        frame.render_widget(Clear, area);
+        frame.render_widget(block, area);
+
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .horizontal_margin(2)
+            .vertical_margin(1)
+            .constraints(
+                [
+                    Constraint::Length(1),
+                    Constraint::Length(3),
+                    Constraint::Length(1),
+                ]
+                .as_ref(),
+            )
+            .split(area);
+
+        frame.render_widget(
+            Paragraph::new(self.hint.as_str()).alignment(Alignment::Center),
+            chunks[0],
+        );
+
+        let mut input_block = Block::default()
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(Color::Yellow));
+        if !self.err_msg.is_empty() {
+            input_block = input_block
+                .title(format!(" {} ", self.err_msg))
+                .title_style(Style::default().fg(Color::Red));
+        }
+        self.input.set_block(input_block);
+        frame.render_widget(self.input.widget(), chunks[1]);
+
+        let footer = Paragraph::new(FOOTER_TEXT).alignment(Alignment::Center);
+        frame.render_widget(footer, chunks[2]);
+    }
+
+    pub fn handle_input(
+        &mut self,
+        key_event: &KeyEvent,
+        app_event_bus: &mpsc::Sender<AppEvent>,
+    ) -> io::Result<()> {
+        match key_event.code {
+            KeyCode::Esc => {
+                app_event_bus.send(AppEvent::CancelClosePopup).unwrap();
+                if let Some(event) = self.purpose.cancel_event() {
+                    app_event_bus.send(event).unwrap();
+                }
+            }
+            KeyCode::Enter => {
+                // passwords and passphrases are taken as typed, spaces
+                // included, an empty passphrase is refused by `validate`
+                let value = if self.input.mask_char().is_some() {
+                    self.input.lines()[0].clone()
+                } else {
+                    let value = self.input.lines()[0].trim().to_string();
+                    if value.is_empty() {
+                        self.err_msg = "Value cannot be empty.".to_string();
+                        return Ok(());
+                    }
+                    value
+                };
+                if let Err(err_msg) = self.purpose.validate(&value) {
+                    self.err_msg = err_msg;
+                    return Ok(());
+                }
+                app_event_bus.send(AppEvent::CancelClosePopup).unwrap();
+                app_event_bus.send(self.purpose.to_event(value)).unwrap();
+            }
+            _ => {
+                self.input.input(*key_event);
+                self.err_msg.clear();
+            }
+        }
+        Ok(())
+    }
+
+    pub fn handle_paste(&mut self, text: &str) {
+        let text: String = text.chars().filter(|c| *c != '\n' && *c != '\r').collect();
+        self.input.insert_str(text);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crossterm::event::KeyModifiers;
+    use pretty_assertions::assert_eq;
+
+    fn submit(popup: &mut InputPopup) -> Vec<AppEvent> {
+        let (tx, rx) = mpsc::channel();
+        popup
+            .handle_input(&KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE), &tx)
+            .unwrap();
+        rx.try_iter().collect()
+    }
+
+    #[test]
+    fn test_masked_value_is_not_trimmed() {
+        let mut popup =
+            InputPopup::masked(" Password ", InputPurpose::ConnectionPassword { id: 3 });
+        popup.handle_paste("  s3cret ");
+        let events = submit(&mut popup);
+        assert!(matches!(
+            events.as_slice(),
+            [AppEvent::CancelClosePopup, AppEvent::ConnectWithPassword { id: 3, password }]
+                if password == "  s3cret "
+        ));
+
+        let mut popup =
+            InputPopup::masked(" Password ", InputPurpose::ConnectionPassword { id: 3 });
+        let events = submit(&mut popup);
+        assert!(matches!(
+            events.as_slice(),
+            [AppEvent::CancelClosePopup, AppEvent::ConnectWithPassword { password, .. }]
+                if password.is_empty()
+        ));
+    }
+
+    #[test]
+    fn test_new_passphrase_is_required_and_repeated() {
+        let mut popup = InputPopup::masked(" Master Passphrase ", InputPurpose::CreateCredentials);
+        assert!(submit(&mut popup).is_empty());
+        assert_eq!(popup.err_msg, "Passphrase cannot be empty.");
+
+        popup.handle_paste("correct horse");
+        let passphrase = match submit(&mut popup).as_slice() {
+            [AppEvent::CancelClosePopup, AppEvent::PassphraseEntered { passphrase }] => {
+                passphrase.clone()
+            }
+            events => panic!("unexpected events {:?}", events),
+        };
+
+        let mut repeat = InputPopup::masked(
+            " Repeat Passphrase ",
+            InputPurpose::RepeatPassphrase { passphrase },
+        );
+        repeat.handle_paste("correct hrose");
+        assert!(submit(&mut repeat).is_empty());
+        assert_eq!(repeat.err_msg, "Passphrases do not match.");
+
+        repeat.input = TextArea::default();
+        repeat.handle_paste("correct horse");
+        assert!(matches!(
+            submit(&mut repeat).as_slice(),
+            [AppEvent::CancelClosePopup, AppEvent::CreateCredentials { passphrase }]
+                if passphrase == "correct horse"
+        ));
+
+        let (tx, rx) = mpsc::channel();
+        repeat
+            .handle_input(&KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE), &tx)
+            .unwrap();
+        assert!(matches!(
+            rx.try_iter().collect::<Vec<_>>().as_slice(),
+            [AppEvent::CancelClosePopup, AppEvent::CredentialsDismissed]
+        ));
+    }
+
+    #[test]
+    fn test_plain_value_is_trimmed_and_required() {
+        let mut popup = InputPopup::new(" Rename ", "  ", InputPurpose::RenameConnection { id: 1 });
+        assert!(submit(&mut popup).is_empty());
+        assert_eq!(popup.err_msg, "Value cannot be empty.");
+
+        popup.handle_paste("prod ");
+        assert!(matches!(
+            submit(&mut popup).as_slice(),
+            [AppEvent::CancelClosePopup, AppEvent::RenameConnection { id: 1, name }]
+                if name == "prod"
+        ));
+    }
+}
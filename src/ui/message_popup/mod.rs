@@ -0,0 +1,92 @@
+use std::{io, sync::mpsc};
+
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{
+    layout::{Alignment, Constraint, Direction, Layout, Rect},
+    style::{Color, Style},
+    widgets::{Block, Borders, Clear, Paragraph, Wrap},
+    Frame,
+};
+
+use crate::AppEvent;
+
+use super::centered_rect_exact_height;
+
+const FOOTER_TEXT: &str = "Enter or Esc: close";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MessageKind {
+    Info,
+    Error,
+}
+
+/// Simple popup used to report the outcome of an action to the user.
+#[derive(Debug)]
+pub struct MessagePopup {
+    title: String,
+    message: String,
+    kind: MessageKind,
+}
+
+impl MessagePopup {
+    pub fn info(title: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            title: title.into(),
+            message: message.into(),
+            kind: MessageKind::Info,
+        }
+    }
+
+    pub fn error(title: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            title: title.into(),
+            message: message.into(),
+            kind: MessageKind::Error,
+        }
+    }
+
+    pub fn render_widget(&mut self, frame: &mut Frame, area: Rect) {
+        let area = centered_rect_exact_height(60, 10, area);
+
+        let color = match self.kind {
+            MessageKind::Info => Color::Green,
+            MessageKind::Error => Color::Red,
+        };
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(color))
+            .title(self.title.as_str());
+
+        frame.render_widget(Clear, area);
+        frame.render_widget(block, area);
+
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .horizontal_margin(2)
+            .vertical_margin(1)
+            .constraints([Constraint::Min(3), Constraint::Length(1)].as_ref())
+            .split(area);
+
+        let message = Paragraph::new(self.message.as_str())
+            .alignment(Alignment::Center)
+            .wrap(Wrap { trim: false });
+        frame.render_widget(message, chunks[0]);
+
+        let footer = Paragraph::new(FOOTER_TEXT).alignment(Alignment::Center);
+        frame.render_widget(footer, chunks[1]);
+    }
+
+    pub fn handle_input(
+        &mut self,
+        key_event: &KeyEvent,
+        app_event_bus: &mpsc::Sender<AppEvent>,
+    ) -> io::Result<()> {
+        match key_event.code {
+            KeyCode::Esc | KeyCode::Enter | KeyCode::Char('q') => {
+                app_event_bus.send(AppEvent::CancelClosePopup).unwrap();
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+}
@@ -1,14 +1,77 @@
-use std::fmt::{self, Debug, Formatter};
+use std::{
+    fmt::{self, Debug, Formatter},
+    io,
+    sync::mpsc,
+};
 
+use confirm_popup::ConfirmPopup;
+use crossterm::event::KeyEvent;
+use import_popup::ImportPopup;
+use input_popup::InputPopup;
+use message_popup::MessagePopup;
 use quit_confirm_popup::QuitConfirmPopup;
+use ratatui::{layout::Rect, Frame};
+use session_switcher_popup::SessionSwitcherPopup;
 
+use crate::AppEvent;
+
+pub mod confirm_popup;
 pub mod connection_popup;
+pub mod fuzzy;
+pub mod import_popup;
+pub mod input_popup;
+pub mod message_popup;
 pub mod quit_confirm_popup;
+pub mod session_switcher_popup;
 pub mod style;
 
 pub enum Popup<'a> {
-    Connection(connection_popup::DbConnectionPopup<'a>),
+    Connection(Box<connection_popup::DbConnectionPopup<'a>>),
     Quit(QuitConfirmPopup),
+    Message(MessagePopup),
+    Confirm(ConfirmPopup),
+    Input(Box<InputPopup<'a>>),
+    SessionSwitcher(Box<SessionSwitcherPopup<'a>>),
+    Import(ImportPopup),
+}
+
+impl<'a> Popup<'a> {
+    pub fn render_widget(&mut self, frame: &mut Frame, area: Rect) {
+        match self {
+            Popup::Connection(popup) => popup.render_widget(frame, area),
+            Popup::Quit(popup) => popup.render_widget(frame, area),
+            Popup::Message(popup) => popup.render_widget(frame, area),
+            Popup::Confirm(popup) => popup.render_widget(frame, area),
+            Popup::Input(popup) => popup.render_widget(frame, area),
+            Popup::SessionSwitcher(popup) => popup.render_widget(frame, area),
+            Popup::Import(popup) => popup.render_widget(frame, area),
+        }
+    }
+
+    pub fn handle_input(
+        &mut self,
+        key_event: &KeyEvent,
+        app_event_bus: &mpsc::Sender<AppEvent>,
+    ) -> io::Result<()> {
+        match self {
+            Popup::Connection(popup) => popup.handle_input(key_event, app_event_bus),
+            Popup::Quit(popup) => popup.handle_input(key_event, app_event_bus),
+            Popup::Message(popup) => popup.handle_input(key_event, app_event_bus),
+            Popup::Confirm(popup) => popup.handle_input(key_event, app_event_bus),
+            Popup::Input(popup) => popup.handle_input(key_event, app_event_bus),
+            Popup::SessionSwitcher(popup) => popup.handle_input(key_event, app_event_bus),
+            Popup::Import(popup) => popup.handle_input(key_event, app_event_bus),
+        }
+    }
+
+    pub fn handle_paste(&mut self, text: &str) {
+        match self {
+            Popup::Connection(popup) => popup.handle_paste(text),
+            Popup::Input(popup) => popup.handle_paste(text),
+            Popup::SessionSwitcher(popup) => popup.handle_paste(text),
+            _ => {}
+        }
+    }
 }
 
 impl<'a> Debug for Popup<'a> {
@@ -22,6 +85,19 @@ impl<'a> Debug for Popup<'a> {
                 )
             }
             Popup::Quit(quit_popup) => write!(f, "New Popup Quit {:?}", quit_popup),
+            Popup::Message(message_popup) => write!(f, "New Popup Message {:?}", message_popup),
+            Popup::Confirm(confirm_popup) => write!(f, "New Popup Confirm {:?}", confirm_popup),
+            Popup::Input(input_popup) => write!(f, "New Popup Input {:?}", input_popup.title()),
+            Popup::SessionSwitcher(_) => write!(f, "New Popup Session Switcher"),
+            Popup::Import(_) => write!(f, "New Popup Import"),
         }
     }
 }
+
+pub fn centered_rect_exact_height(percent_x: u16, height: u16, area: Rect) -> Rect {
+    let popup_width = area.width * percent_x / 100;
+    let popup_height = height.min(area.height);
+    let popup_x = (area.width - popup_width) / 2;
+    let popup_y = (area.height - popup_height) / 2;
+    Rect::new(popup_x, popup_y, popup_width, popup_height)
+}
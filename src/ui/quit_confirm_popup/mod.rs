@@ -10,6 +10,8 @@ use ratatui::{
 
 use crate::AppEvent;
 
+use super::centered_rect_exact_height;
+
 const FOOTER_TEXT: &str = "Q or Enter: quit | Esc or C: Cancel";
 const FOOTER_MARGIN: u16 = 15;
 
@@ -85,11 +87,3 @@ impl QuitConfirmPopup {
         app_event_bus.send(AppEvent::ConfirmQuitApp).unwrap();
     }
 }
-
-fn centered_rect_exact_height(percent_x: u16, height: u16, area: Rect) -> Rect {
-    let popup_width = area.width * percent_x / 100;
-    let popup_height = height;
-    let popup_x = (area.width - popup_width) / 2;
-    let popup_y = (area.height - popup_height) / 2;
-    Rect::new(popup_x, popup_y, popup_width, popup_height)
-}
@@ -0,0 +1,284 @@
+use std::{io, sync::mpsc};
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::{
+    layout::{Alignment, Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph},
+    Frame,
+};
+use tui_textarea::TextArea;
+
+use crate::AppEvent;
+
+use super::{
+    centered_rect_exact_height,
+    fuzzy::{fuzzy_filter, highlighted, FuzzyMatch},
+};
+
+const FOOTER_TEXT: &str = "Enter: switch | Ctrl-x: close connection | Esc: cancel";
+
+/// One open session as listed in the switcher.
+#[derive(Debug, Clone)]
+pub struct SessionEntry {
+    pub id: u32,
+    /// Searched and displayed, e.g. `orders-db [PostgreSQL] app@db:5432/orders`.
+    pub label: String,
+    /// `[PROD]`, `[RO]`, ... shown in front of the label.
+    pub badge: String,
+    pub badge_style: Style,
+    pub active: bool,
+}
+
+/// Lists the open sessions, narrowed down by a fuzzy filter, to jump between them.
+#[derive(Debug)]
+pub struct SessionSwitcherPopup<'a> {
+    filter: TextArea<'a>,
+    entries: Vec<SessionEntry>,
+    /// Indices into `entries` that match the filter, best first.
+    matches: Vec<(usize, FuzzyMatch)>,
+    state: ListState,
+}
+
+impl<'a> SessionSwitcherPopup<'a> {
+    pub fn new(entries: Vec<SessionEntry>) -> Self {
+        let mut filter = TextArea::default();
+        filter.set_cursor_line_style(Style::default());
+        filter.set_cursor_style(Style::default().bg(Color::White).fg(Color::Black));
+
+        let mut popup = Self {
+            filter,
+            entries,
+            matches: vec![],
+            state: ListState::default(),
+        };
+        popup.update_matches();
+        // start on the session after the active one, so Enter toggles between two
+        if let Some(active) = popup.entries.iter().position(|entry| entry.active) {
+            popup.state.select(Some((active + 1) % popup.entries.len()));
+        }
+        popup
+    }
+
+    fn pattern(&self) -> &str {
+        &self.filter.lines()[0]
+    }
+
+    fn update_matches(&mut self) {
+        let pattern = self.pattern().to_string();
+        self.matches = fuzzy_filter(
+            &pattern,
+            self.entries
+                .iter()
+                .enumerate()
+                .map(|(i, entry)| (i, entry.label.as_str())),
+        );
+        self.state.select(if self.matches.is_empty() {
+            None
+        } else {
+            Some(0)
+        });
+    }
+
+    fn selected_entry(&self) -> Option<&SessionEntry> {
+        let (index, _) = self.matches.get(self.state.selected()?)?;
+        self.entries.get(*index)
+    }
+
+    fn select_next(&mut self) {
+        if self.matches.is_empty() {
+            return;
+        }
+        let i = self
+            .state
+            .selected()
+            .map_or(0, |i| (i + 1) % self.matches.len());
+        self.state.select(Some(i));
+    }
+
+    fn select_previous(&mut self) {
+        if self.matches.is_empty() {
+            return;
+        }
+        let i = self.state.selected().map_or(0, |i| {
+            if i == 0 {
+                self.matches.len() - 1
+            } else {
+                i - 1
+            }
+        });
+        self.state.select(Some(i));
+    }
+
+    pub fn render_widget(&mut self, frame: &mut Frame, area: Rect) {
+        let height = self.entries.len() as u16 + 7;
+        let area = centered_rect_exact_height(60, height.max(10), area);
+
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title(" Open Connections ");
+
+        frame.render_widget(Clear, area);
+        frame.render_widget(block, area);
+
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .horizontal_margin(2)
+            .vertical_margin(1)
+            .constraints(
+                [
+                    Constraint::Length(3),
+                    Constraint::Min(1),
+                    Constraint::Length(1),
+                ]
+                .as_ref(),
+            )
+            .split(area);
+
+        self.filter.set_block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(Color::Yellow))
+                .title(" Filter "),
+        );
+        frame.render_widget(self.filter.widget(), chunks[0]);
+
+        let items: Vec<ListItem> = self
+            .matches
+            .iter()
+            .map(|(index, fuzzy_match)| {
+                let entry = &self.entries[*index];
+                let mut spans = vec![Span::raw(if entry.active { "* " } else { "  " })];
+                if !entry.badge.is_empty() {
+                    spans.push(Span::styled(entry.badge.clone(), entry.badge_style));
+                    spans.push(Span::raw(" "));
+                }
+                spans.extend(highlighted(
+                    &entry.label,
+                    &fuzzy_match.indices,
+                    Style::default(),
+                ));
+                ListItem::new(Line::from(spans))
+            })
+            .collect();
+        let list = List::new(items)
+            .highlight_style(Style::default().add_modifier(Modifier::REVERSED))
+            .highlight_symbol("> ");
+        frame.render_stateful_widget(list, chunks[1], &mut self.state);
+
+        let footer = Paragraph::new(FOOTER_TEXT).alignment(Alignment::Center);
+        frame.render_widget(footer, chunks[2]);
+    }
+
+    pub fn handle_input(
+        &mut self,
+        key_event: &KeyEvent,
+        app_event_bus: &mpsc::Sender<AppEvent>,
+    ) -> io::Result<()> {
+        let ctrl = key_event.modifiers.contains(KeyModifiers::CONTROL);
+        match key_event.code {
+            KeyCode::Esc => app_event_bus.send(AppEvent::CancelClosePopup).unwrap(),
+            KeyCode::Enter => {
+                if let Some(entry) = self.selected_entry() {
+                    app_event_bus.send(AppEvent::CancelClosePopup).unwrap();
+                    app_event_bus
+                        .send(AppEvent::SwitchSession { id: entry.id })
+                        .unwrap();
+                }
+            }
+            KeyCode::Char('x') if ctrl => {
+                if let Some(entry) = self.selected_entry() {
+                    let id = entry.id;
+                    app_event_bus.send(AppEvent::CloseSession { id }).unwrap();
+                    self.entries.retain(|entry| entry.id != id);
+                    if self.entries.is_empty() {
+                        app_event_bus.send(AppEvent::CancelClosePopup).unwrap();
+                    }
+                    self.update_matches();
+                }
+            }
+            KeyCode::Down => self.select_next(),
+            KeyCode::Char('n') if ctrl => self.select_next(),
+            KeyCode::Up => self.select_previous(),
+            KeyCode::Char('p') if ctrl => self.select_previous(),
+            _ => {
+                if self.filter.input(*key_event) {
+                    self.update_matches();
+                }
+            }
+        }
+        Ok(())
+    }
+
+    pub fn handle_paste(&mut self, text: &str) {
+        let text: String = text.chars().filter(|c| *c != '\n' && *c != '\r').collect();
+        self.filter.insert_str(text);
+        self.update_matches();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn entry(id: u32, label: &str, active: bool) -> SessionEntry {
+        SessionEntry {
+            id,
+            label: label.to_string(),
+            badge: String::new(),
+            badge_style: Style::default(),
+            active,
+        }
+    }
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    #[test]
+    fn test_filter_and_switch() {
+        let (tx, rx) = mpsc::channel();
+        let mut popup = SessionSwitcherPopup::new(vec![
+            entry(1, "orders-staging", true),
+            entry(2, "orders-prod", false),
+            entry(3, "analytics", false),
+        ]);
+        // the next session is preselected
+        assert_eq!(popup.selected_entry().unwrap().id, 2);
+
+        for c in "anl".chars() {
+            popup.handle_input(&key(KeyCode::Char(c)), &tx).unwrap();
+        }
+        assert_eq!(popup.matches.len(), 1);
+
+        popup.handle_input(&key(KeyCode::Enter), &tx).unwrap();
+        assert!(matches!(rx.try_recv(), Ok(AppEvent::CancelClosePopup)));
+        assert!(matches!(
+            rx.try_recv(),
+            Ok(AppEvent::SwitchSession { id: 3 })
+        ));
+    }
+
+    #[test]
+    fn test_close_session() {
+        let (tx, rx) = mpsc::channel();
+        let mut popup = SessionSwitcherPopup::new(vec![entry(1, "a", true), entry(2, "b", false)]);
+
+        let ctrl_x = KeyEvent::new(KeyCode::Char('x'), KeyModifiers::CONTROL);
+        popup.handle_input(&ctrl_x, &tx).unwrap();
+        assert!(matches!(
+            rx.try_recv(),
+            Ok(AppEvent::CloseSession { id: 2 })
+        ));
+        assert_eq!(popup.entries.len(), 1);
+
+        popup.handle_input(&ctrl_x, &tx).unwrap();
+        assert!(matches!(
+            rx.try_recv(),
+            Ok(AppEvent::CloseSession { id: 1 })
+        ));
+        assert!(matches!(rx.try_recv(), Ok(AppEvent::CancelClosePopup)));
+    }
+}
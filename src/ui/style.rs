@@ -1,13 +1,17 @@
 use anyhow::Result;
 use ratatui::style::{Color, Modifier, Style};
-use ron::ser::{to_string_pretty, PrettyConfig};
 use serde::{Deserialize, Serialize};
-use std::{fs::File, io::Write, path::PathBuf, rc::Rc};
+use std::{fs::File, path::PathBuf, rc::Rc};
 use struct_patch::Patch;
 
+use crate::db::{connection::Environment, health::HealthStatus};
+
 pub type SharedTheme = Rc<Theme>;
 
-#[derive(Debug)]
+/// Colours of the UI. `theme.ron` only needs to list the values that differ
+/// from the defaults, e.g. `(env_prod: Some("LightRed"))`.
+#[derive(Debug, Clone, Serialize, Deserialize, Patch)]
+#[patch_derive(Debug, Default, Serialize, Deserialize)]
 pub struct Theme {
     selected_tab: Color,
     command_fg: Color,
@@ -20,6 +24,14 @@ pub struct Theme {
     danger_fg: Color,
     line_break: String,
     block_title_focused: Color,
+    env_dev: Color,
+    env_staging: Color,
+    env_prod: Color,
+    env_custom: Color,
+    read_only_badge: Color,
+    health_connected: Color,
+    health_degraded: Color,
+    health_disconnected: Color,
 }
 
 impl Theme {
@@ -98,8 +110,65 @@ impl Theme {
         Style::default().fg(Color::Yellow)
     }
 
+    fn environment_color(&self, environment: &Environment) -> Option<Color> {
+        match environment {
+            Environment::None => None,
+            Environment::Dev => Some(self.env_dev),
+            Environment::Staging => Some(self.env_staging),
+            Environment::Prod => Some(self.env_prod),
+            Environment::Custom(_) => Some(self.env_custom),
+        }
+    }
+
+    /// Label text in the colour of the environment, e.g. the `[prod]` badge.
+    pub fn environment(&self, environment: &Environment) -> Style {
+        match self.environment_color(environment) {
+            Some(color) => Style::default().fg(color).add_modifier(Modifier::BOLD),
+            None => Style::default(),
+        }
+    }
+
+    /// Bar filled with the colour of the environment, used for the title bar.
+    pub fn environment_bar(&self, environment: &Environment) -> Style {
+        match self.environment_color(environment) {
+            Some(color) => Style::default()
+                .bg(color)
+                .fg(Color::Black)
+                .add_modifier(Modifier::BOLD),
+            None => Style::default(),
+        }
+    }
+
+    /// The `RO` badge shown while the session is read-only.
+    pub fn read_only_badge(&self) -> Style {
+        Style::default()
+            .bg(self.read_only_badge)
+            .fg(Color::Black)
+            .add_modifier(Modifier::BOLD)
+    }
+
+    /// The dot showing whether a session is connected, degraded or disconnected.
+    pub fn health(&self, status: HealthStatus) -> Style {
+        Style::default().fg(match status {
+            HealthStatus::Connected => self.health_connected,
+            HealthStatus::Degraded => self.health_degraded,
+            HealthStatus::Disconnected => self.health_disconnected,
+        })
+    }
+
+    fn load_patch(theme_path: &PathBuf) -> Result<ThemePatch> {
+        let file = File::open(theme_path)?;
+        Ok(ron::de::from_reader(file)?)
+    }
+
+    /// Loads `theme_path` on top of the default theme. A missing or broken file
+    /// leaves the defaults in place.
     pub fn init(theme_path: &PathBuf) -> Self {
         let mut theme = Self::default();
+        match Self::load_patch(theme_path) {
+            Ok(patch) => theme.apply(patch),
+            Err(e) => log::info!("theme not loaded from {:?}: {}", theme_path, e),
+        }
         theme
     }
 }
@@ -118,6 +187,14 @@ impl Default for Theme {
             danger_fg: Color::Red,
             line_break: "¶".to_string(),
             block_title_focused: Color::Reset,
+            env_dev: Color::Green,
+            env_staging: Color::Yellow,
+            env_prod: Color::Red,
+            env_custom: Color::Cyan,
+            read_only_badge: Color::Magenta,
+            health_connected: Color::Green,
+            health_degraded: Color::Yellow,
+            health_disconnected: Color::Red,
         }
     }
 }
@@ -126,6 +203,7 @@ impl Default for Theme {
 mod tests {
     use super::*;
     use pretty_assertions::assert_eq;
+    use std::io::Write;
     use tempfile::NamedTempFile;
 
     #[test]
@@ -134,11 +212,11 @@ mod tests {
 
         writeln!(
             file,
-            r"
+            r#"
 (
-	selection_bg: Some(White),
+	selection_bg: Some("White"),
 )
-"
+"#
         )
         .unwrap();
 